@@ -1,58 +1,64 @@
-use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use crate::raw::Heap;
+use crate::shim::{Arc, AtomicPtr, AtomicUsize, Mutex, Ordering};
+#[cfg(test)]
+use crate::shim::thread;
 
-struct ThreadSafeData {
+pub struct ThreadSafeData {
     // 使用Mutex来保护字符串，使其线程安全
     message: Mutex<String>,
     // 使用AtomicUsize来实现线程安全的计数器
     counter: Arc<AtomicUsize>,
-    ptr: Arc<AtomicPtr<String>>,
+    ptr: Arc<AtomicPtr<Heap<String>>>,
 }
 
 impl ThreadSafeData {
-    fn new(message: String) -> ThreadSafeData {
+    pub fn new(message: String) -> ThreadSafeData {
         ThreadSafeData {
             message: Mutex::new(message),
             counter: Arc::new(AtomicUsize::new(0)),
-            ptr: Arc::new(AtomicPtr::new(Box::into_raw(Box::new(
-                "AtomicPtr>>>".to_string(),
-            )))),
+            ptr: Arc::new(AtomicPtr::new(Heap::into_raw("AtomicPtr>>>".to_string()))),
         }
     }
 
     // 增加计数的方法，使用原子操作
-    fn increment_counter(&self) {
+    pub fn increment_counter(&self) {
         self.counter
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
     }
 
     // 修改消息的方法，使用Mutex保护
-    fn update_message(&self, new_message: &str) {
+    pub fn update_message(&self, new_message: &str) {
         let mut msg = self.message.lock().unwrap();
         *msg = new_message.to_string();
     }
 
     // 获取消息的方法，同样使用Mutex保护
-    fn get_message(&self) -> String {
+    pub fn get_message(&self) -> String {
         self.message.lock().unwrap().clone()
     }
 
     // 获取计数的方法，直接读取原子变量
-    fn get_counter(&self) -> usize {
+    pub fn get_counter(&self) -> usize {
         self.counter.load(Ordering::SeqCst)
     }
 
-    fn get_pre(&self) -> String {
+    pub fn get_pre(&self) -> String {
         let ptr = self.ptr.load(Ordering::Acquire);
         if ptr.is_null() {
             String::new()
         } else {
-            unsafe { (*ptr).clone() }
+            unsafe { Heap::with(ptr, |s| s.clone()) }
         }
     }
 }
 
+impl Drop for ThreadSafeData {
+    fn drop(&mut self) {
+        let ptr = self.ptr.load(Ordering::Relaxed);
+        unsafe { Heap::free(ptr) };
+    }
+}
+
 #[test]
 fn testmain() {
     let shared_data = Arc::new(ThreadSafeData::new("Initial Message".to_string()));
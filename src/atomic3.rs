@@ -1,27 +1,35 @@
-use std::ptr;
-use std::sync::atomic::{AtomicPtr, Ordering};
-use std::sync::Arc;
-use std::thread;
+use crate::raw::Heap;
+use crate::shim::{AtomicPtr, Backoff, Ordering, Readers};
+use alloc::string::{String, ToString};
+use core::fmt;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(test)]
+use crate::stress::StressRun;
 
-struct SharedString {
-    ptr: AtomicPtr<String>,
+pub struct SharedString {
+    ptr: AtomicPtr<Heap<String>>,
+    // 正在读取的读者数，旧字符串要等读者都离开后才能删除
+    readers: Readers,
 }
 
 impl SharedString {
     //创建一个AtomicPtr 指针对象
-    fn new(s: String) -> Self {
+    pub fn new(s: String) -> Self {
         SharedString {
-            ptr: AtomicPtr::new(Box::into_raw(Box::new(s))),
+            ptr: AtomicPtr::new(Heap::into_raw(s)),
+            readers: Readers::new(),
             //使用Box::new将这个String实例装箱。装箱操作会将值放置在堆上，并返回一个指向该值的智能指针（Box）。
             //这样做的原因是String作为复杂类型，其大小不固定，不能直接存放在栈上作为原生指针的目标。
             //装箱还负责String的生命周期管理，当Box离开作用域时，它会自动清理堆上的内存
             //然后，使用Box::into_raw方法将Box<String>转换为一个原始指针（*mut String）。
             //这一步实际上剥离了智能指针的自动内存管理特性，将控制权交给了调用者，意味着你现在需要手动管理这个指针的生命周期，包括分配和释放
+            //Heap::into_raw 内部做的就是这两步
         }
     }
 
-    fn update(&self, new_val: String) {
-        let new_ptr = Box::into_raw(Box::new(new_val));
+    pub fn update(&self, new_val: String) {
+        let new_ptr = Heap::into_raw(new_val);
+        let mut backoff = Backoff::new();
         loop {  //自旋：自旋在预期锁被持有的时间很短的情况下可以减少上下文切换的开销，但如果锁被长时间持有，自旋会浪费CPU周期，导致忙等待，影响整体性能，并可能增加系统功耗
             let current_ptr = self.ptr.load(Ordering::Acquire);
             // 尝试原子性地更新指针，这里简化处理，实际应确保旧值仍然有效
@@ -30,46 +38,120 @@ impl SharedString {
                 .compare_exchange_weak(current_ptr, new_ptr, Ordering::Release, Ordering::Relaxed)
                 .is_ok()
             {
-                // 如果成功，安全地删除旧字符串
+                // 如果成功，等还在读旧字符串的读者离开后，安全地删除旧字符串
+                self.readers.wait();
                 unsafe {
-                    drop(Box::from_raw(current_ptr));
+                    Heap::free(current_ptr);
                 }
                 break;
-            } 
+            }
+            backoff.snooze();
         }
     }
 
-    fn get(&self) -> String {
+    pub fn get(&self) -> String {
+        let _guard = self.readers.enter();
         let ptr = self.ptr.load(Ordering::Acquire); //使用load方法以适当的内存顺序从AtomicPtr中安全地加载原始指针
         if !ptr.is_null() {
             //要在unsafe代码块中正确解引用并转换为String，需要克隆这个String的值
-            unsafe { (*ptr).clone() }
+            unsafe { Heap::with(ptr, |s| s.clone()) }
         } else {
             String::new()
         }
     }
 }
 
-#[test]
-fn testmain() {
-    let shared_str = Arc::new(SharedString::new("Initial Value".to_string()));
-    let mut handles = vec![];
-    for i in 0..50 {
-        // 直接在这里克隆，确保每次迭代都有独立的Arc实例
-        let shared_str_clone = shared_str.clone();
-        let update_thread = thread::spawn(move || {
-            let new_val = format!("Value-{}", i);
-            println!("{}", new_val);
-            shared_str_clone.update(new_val);
-            thread::sleep(std::time::Duration::from_millis(100));
-        });
-        handles.push(update_thread);
+impl Drop for SharedString {
+    fn drop(&mut self) {
+        let ptr = self.ptr.load(Ordering::Relaxed);
+        unsafe { Heap::free(ptr) };
+    }
+}
+
+impl fmt::Debug for SharedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedString")
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+impl fmt::Display for SharedString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.get())
+    }
+}
+
+impl Default for SharedString {
+    fn default() -> Self {
+        SharedString::new(String::new())
+    }
+}
+
+impl From<String> for SharedString {
+    fn from(s: String) -> Self {
+        SharedString::new(s)
+    }
+}
+
+impl From<&str> for SharedString {
+    fn from(s: &str) -> Self {
+        SharedString::new(s.to_string())
+    }
+}
+
+// 克隆时深拷贝当前值，得到一个独立的新指针
+impl Clone for SharedString {
+    fn clone(&self) -> Self {
+        SharedString::new(self.get())
     }
+}
 
-    // 等待所有线程完成
-    for handle in handles {
-        handle.join().unwrap();
+impl Serialize for SharedString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.get())
     }
+}
+
+impl<'de> Deserialize<'de> for SharedString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(SharedString::new)
+    }
+}
+
+#[test]
+fn testmain() {
+    let shared_str = SharedString::new("Initial Value".to_string());
+
+    // 一半的迭代更新，一半的迭代读取，由各线程的随机数决定
+    let report = StressRun::new().threads(8).iterations(200).run(|w| {
+        if w.rng().below(2) == 0 {
+            shared_str.update(format!("Value-{}", w.index()));
+        } else {
+            let value = shared_str.get();
+            assert!(value == "Initial Value" || value.starts_with("Value-"), "{}", value);
+        }
+    });
+    println!("{}", report.assert_ok());
 
     println!("Final Value: {}", shared_str.get());
 }
+
+#[test]
+fn test_traits() {
+    let shared_str = SharedString::from("Initial Value");
+    assert_eq!(
+        format!("{:?}", shared_str),
+        r#"SharedString { value: "Initial Value" }"#
+    );
+    assert_eq!(shared_str.to_string(), "Initial Value");
+
+    let copy = shared_str.clone();
+    shared_str.update("Value-1".to_string());
+    assert_eq!(copy.get(), "Initial Value");
+
+    let json = serde_json::to_string(&shared_str).unwrap();
+    let back: SharedString = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.get(), "Value-1");
+    assert_eq!(SharedString::default().get(), "");
+}
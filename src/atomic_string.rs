@@ -0,0 +1,625 @@
+use crate::raw::Heap;
+#[cfg(all(test, feature = "std"))]
+use crate::shim::thread;
+#[cfg(test)]
+use crate::stress::StressRun;
+use crate::shim::{fence, Arc, AtomicPtr, AtomicU8, AtomicUsize, Backoff, Ordering, Readers};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use alloc::borrow::Cow;
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+// 默认内联容量：不超过该字节数的字符串直接存放在内联缓冲区中，不做堆分配
+const DEFAULT_INLINE_CAPACITY: usize = 22;
+
+pub struct AtomicString {
+    // 顺序锁（seqlock）序号：偶数表示空闲，奇数表示有写者正在写入；每次更新加 2
+    version: Arc<AtomicUsize>,
+    // 堆上的值；为空指针时表示当前值存放在内联缓冲区中
+    ptr: Arc<AtomicPtr<Heap<String>>>,
+    // 正在读取的读者数，写者要等它归零后才能释放被替换下来的旧值
+    readers: Readers,
+    // 内联缓冲区及其中有效数据的长度，受 version 顺序锁保护
+    inline: Box<[AtomicU8]>,
+    inline_len: AtomicUsize,
+    // 公平模式的写者排队号（ticket）：next_ticket 为下一个可领取的号，now_serving 为当前轮到的号
+    #[cfg(feature = "std")]
+    next_ticket: AtomicUsize,
+    #[cfg(feature = "std")]
+    now_serving: AtomicUsize,
+}
+
+// 待发布的新值：短值稍后拷入内联缓冲区，长值在加锁前就装箱好，缩短持锁时间
+enum Staged<'a> {
+    Inline(Cow<'a, str>),
+    Heap(*mut Heap<String>),
+}
+
+impl AtomicString {
+    pub fn new(s: String) -> Self {
+        Self::with_inline_capacity(s, DEFAULT_INLINE_CAPACITY)
+    }
+
+    // 指定内联容量创建；capacity 为 0 时所有值都放在堆上
+    pub fn with_inline_capacity(s: String, capacity: usize) -> Self {
+        let vs = AtomicString {
+            ptr: Arc::new(AtomicPtr::new(core::ptr::null_mut())),
+            version: Arc::new(AtomicUsize::new(0)),
+            inline: (0..capacity).map(|_| AtomicU8::new(0)).collect(),
+            inline_len: AtomicUsize::new(0),
+            readers: Readers::new(),
+            #[cfg(feature = "std")]
+            next_ticket: AtomicUsize::new(0),
+            #[cfg(feature = "std")]
+            now_serving: AtomicUsize::new(0),
+        };
+        // 还没有共享给其他线程，不需要加锁
+        let staged = vs.stage(Cow::Owned(s));
+        vs.publish(staged);
+        vs
+    }
+
+    pub fn update(&self, new_val: String) {
+        self.write(Cow::Owned(new_val));
+    }
+
+    // 与 update 相同，但直接接收 &str：短值整个更新过程都不会分配内存
+    pub fn update_str(&self, new_val: &str) {
+        self.write(Cow::Borrowed(new_val));
+    }
+
+    // 只尝试一次获取写锁，失败时把新值原样退还给调用方
+    pub fn try_update(&self, new_val: String) -> Result<(), String> {
+        match self.try_lock() {
+            Some(seq) => {
+                self.commit(seq, self.stage(Cow::Owned(new_val)));
+                Ok(())
+            }
+            None => Err(new_val),
+        }
+    }
+
+    // 在 timeout 时间内反复尝试更新，超时仍未拿到写锁则放弃并退还新值。
+    // timeout 只限制获取写锁：拿到写锁后新值就已发布，之后还要等读者离开才能释放旧值，
+    // 这段等待不受 timeout 限制，读者持续不断时返回 Ok 之前可能远远超过 timeout
+    #[cfg(feature = "std")]
+    pub fn try_update_for(&self, new_val: String, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        let mut backoff = Backoff::new();
+        loop {
+            if let Some(seq) = self.try_lock() {
+                self.commit(seq, self.stage(Cow::Owned(new_val)));
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(new_val);
+            }
+            backoff.snooze();
+        }
+    }
+
+    fn write(&self, new_val: Cow<'_, str>) {
+        let staged = self.stage(new_val);
+        let mut backoff = Backoff::new();
+        loop {
+            if let Some(seq) = self.try_lock() {
+                self.commit(seq, staged);
+                break;
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    // 持有写锁时发布新值并解锁，再释放被替换下来的旧值
+    fn commit(&self, seq: usize, staged: Staged<'_>) {
+        let old_ptr = self.publish(staged);
+        self.unlock(seq);
+        if !old_ptr.is_null() {
+            self.readers.wait();
+            unsafe { Heap::free(old_ptr) };
+        }
+    }
+
+    fn stage<'a>(&self, new_val: Cow<'a, str>) -> Staged<'a> {
+        if new_val.len() <= self.inline.len() {
+            Staged::Inline(new_val)
+        } else {
+            Staged::Heap(Heap::into_raw(new_val.into_owned()))
+        }
+    }
+
+    // 尝试获取写锁：把偶数序号改为奇数，成功时返回加锁前的序号
+    fn try_lock(&self) -> Option<usize> {
+        let seq = self.version.load(Ordering::Relaxed);
+        if seq & 1 == 1 {
+            return None;
+        }
+        // 使用强 CAS，避免 try_update 因伪失败而白白放弃
+        let locked = self
+            .version
+            .compare_exchange(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed)
+            .ok();
+        if locked.is_some() {
+            // 读者只要看到了本次写入的任何数据，之后就一定能看到奇数序号
+            fence(Ordering::Release);
+        }
+        locked
+    }
+
+    fn unlock(&self, seq: usize) {
+        self.version.store(seq.wrapping_add(2), Ordering::Release);
+    }
+
+    // 在持有写锁时写入新值，返回被替换下来的堆指针（可能为空），由调用方在解锁后释放
+    fn publish(&self, staged: Staged<'_>) -> *mut Heap<String> {
+        match staged {
+            Staged::Inline(s) => {
+                for (slot, b) in self.inline.iter().zip(s.bytes()) {
+                    slot.store(b, Ordering::Relaxed);
+                }
+                self.inline_len.store(s.len(), Ordering::Relaxed);
+                self.ptr.swap(core::ptr::null_mut(), Ordering::Release)
+            }
+            Staged::Heap(new_ptr) => self.ptr.swap(new_ptr, Ordering::Release),
+        }
+    }
+
+    // 公平模式更新：写者按领号顺序（FIFO）依次写入，不会被后来者反复抢先。读者不参与排队，
+    // get 仍然是无锁的。公平只保证写者之间的先后顺序，不保证等待时间有上限：
+    // 每个写者写完后要等读者离开、释放旧值才叫下一个号，读者持续不断时这段等待没有上限。
+    // 返回值为该写者从领号到轮到自己所等待的时间。
+    // 注意：只有全部写者都使用 update_fair 时才能保证公平，混用 update 的写者仍可能插队。
+    // 统计等待时间需要时钟，因此公平模式只在启用 std 时提供
+    #[cfg(feature = "std")]
+    pub fn update_fair(&self, new_val: String) -> Duration {
+        self.fair_turn(|| self.update(new_val))
+    }
+
+    // 领号排队，轮到自己时执行 f，然后叫下一个号；返回排队等待的时间
+    #[cfg(feature = "std")]
+    fn fair_turn(&self, f: impl FnOnce()) -> Duration {
+        let start = Instant::now();
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        let mut backoff = Backoff::new();
+        while self.now_serving.load(Ordering::Acquire) != ticket {
+            backoff.snooze();
+        }
+        let waited = start.elapsed();
+
+        f();
+
+        // 叫下一个号
+        self.now_serving
+            .store(ticket.wrapping_add(1), Ordering::Release);
+        waited
+    }
+
+    pub fn get(&self) -> String {
+        let mut s = String::new();
+        self.get_into(&mut s);
+        s
+    }
+
+    // 把当前值读到调用方提供的 String 中，复用其已有容量；内联的短值读取不会分配内存
+    pub fn get_into(&self, out: &mut String) {
+        self.snapshot_into(out);
+    }
+
+    // 同时读出当前值和它对应的版本号，两者保证一致
+    pub fn snapshot(&self) -> (String, usize) {
+        let mut s = String::new();
+        let version = self.snapshot_into(&mut s);
+        (s, version)
+    }
+
+    fn snapshot_into(&self, out: &mut String) -> usize {
+        let mut buf = core::mem::take(out).into_bytes();
+        let mut backoff = Backoff::new();
+        loop {
+            buf.clear();
+            let seq = self.version.load(Ordering::Acquire);
+            if seq & 1 == 0 {
+                let guard = self.readers.enter();
+                let ptr = self.ptr.load(Ordering::Acquire);
+                if ptr.is_null() {
+                    let len = self
+                        .inline_len
+                        .load(Ordering::Relaxed)
+                        .min(self.inline.len());
+                    buf.extend(self.inline[..len].iter().map(|b| b.load(Ordering::Relaxed)));
+                } else {
+                    unsafe { Heap::with(ptr, |s| buf.extend_from_slice(s.as_bytes())) };
+                }
+                drop(guard);
+                // 读完后序号没变，说明期间没有写者，读到的是一个完整的值
+                fence(Ordering::Acquire);
+                if self.version.load(Ordering::Relaxed) == seq {
+                    match String::from_utf8(buf) {
+                        Ok(s) => {
+                            *out = s;
+                            return seq >> 1;
+                        }
+                        Err(e) => buf = e.into_bytes(),
+                    }
+                }
+            }
+            backoff.snooze();
+        }
+    }
+
+    // 已完成的更新次数
+    pub fn version(&self) -> usize {
+        self.version.load(Ordering::Acquire) >> 1
+    }
+}
+
+impl Drop for AtomicString {
+    fn drop(&mut self) {
+        let ptr = self.ptr.load(Ordering::Relaxed);
+        unsafe { Heap::free(ptr) };
+    }
+}
+
+// 以下标准 trait 都基于一次一致的快照实现
+impl fmt::Debug for AtomicString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (value, version) = self.snapshot();
+        f.debug_struct("AtomicString")
+            .field("value", &value)
+            .field("version", &version)
+            .finish()
+    }
+}
+
+impl fmt::Display for AtomicString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.get())
+    }
+}
+
+impl Default for AtomicString {
+    fn default() -> Self {
+        AtomicString::new(String::new())
+    }
+}
+
+impl From<String> for AtomicString {
+    fn from(s: String) -> Self {
+        AtomicString::new(s)
+    }
+}
+
+impl From<&str> for AtomicString {
+    fn from(s: &str) -> Self {
+        AtomicString::new(s.to_string())
+    }
+}
+
+// 克隆得到的是一个独立的新单元：深拷贝当前值，沿用相同的内联容量，版本号从 0 开始
+impl Clone for AtomicString {
+    fn clone(&self) -> Self {
+        AtomicString::with_inline_capacity(self.get(), self.inline.len())
+    }
+}
+
+impl Serialize for AtomicString {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.get())
+    }
+}
+
+impl<'de> Deserialize<'de> for AtomicString {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(AtomicString::new)
+    }
+}
+
+#[test]
+fn testvs() {
+    let vstring = AtomicString::new("Initial Message".to_string());
+
+    let report = StressRun::new().iterations(100).run(|w| {
+        let new_value = format!("Value updated {}", w.rng().below(100));
+        vstring.update(new_value);
+        assert!(vstring.get().starts_with("Value updated "));
+    });
+    println!("{}", report.assert_ok());
+
+    // 每次迭代更新一次，版本号不能丢
+    assert_eq!(vstring.version() as u64, report.operations());
+    println!("Final get: {}", vstring.get());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_update_fair() {
+    let vstring = Arc::new(AtomicString::new("Initial Message".to_string()));
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    // 先占住 0 号，让所有写者都在队列里排队
+    let gate = vstring.next_ticket.fetch_add(1, Ordering::SeqCst);
+    let handles: Vec<_> = (1..=100)
+        .map(|i| {
+            let str_clone = vstring.clone();
+            let order = order.clone();
+            let handle = thread::spawn(move || {
+                str_clone.fair_turn(|| {
+                    order.lock().unwrap().push(i);
+                    str_clone.update(format!("Value updated {}", i));
+                })
+            });
+            // 等这个写者领到 i 号后再启动下一个，排队号与 i 一一对应
+            while vstring.next_ticket.load(Ordering::SeqCst) <= i {
+                thread::yield_now();
+            }
+            handle
+        })
+        .collect();
+    vstring.now_serving.store(gate + 1, Ordering::SeqCst);
+
+    // 收集每个写者的等待时间
+    let mut waits: Vec<Duration> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    waits.sort();
+
+    // 写者严格按领号顺序轮到
+    assert_eq!(*order.lock().unwrap(), (1..=100).collect::<Vec<_>>());
+    assert_eq!(vstring.next_ticket.load(Ordering::SeqCst), 101);
+    assert_eq!(vstring.now_serving.load(Ordering::SeqCst), 101);
+    assert_eq!(vstring.version(), 100);
+    assert_eq!(vstring.get(), "Value updated 100");
+    println!(
+        "Fair writers waited: min {:?}, median {:?}, max {:?}",
+        waits[0],
+        waits[waits.len() / 2],
+        waits[waits.len() - 1]
+    );
+}
+
+#[test]
+fn test_inline_and_heap() {
+    let vstring = AtomicString::with_inline_capacity("ok".to_string(), 8);
+    assert!(vstring.ptr.load(Ordering::SeqCst).is_null()); // 短值存放在内联缓冲区
+    assert_eq!(vstring.get(), "ok");
+
+    vstring.update("a much longer value".to_string());
+    assert!(!vstring.ptr.load(Ordering::SeqCst).is_null()); // 超出容量，回退到堆上
+    assert_eq!(vstring.get(), "a much longer value");
+
+    vstring.update_str("降级"); // 6 个字节，仍然可以内联
+    assert!(vstring.ptr.load(Ordering::SeqCst).is_null());
+    assert_eq!(vstring.get(), "降级");
+    assert_eq!(vstring.version(), 2);
+}
+
+#[test]
+fn test_inline_concurrent() {
+    // 并发读写短值，读者只能看到完整写入的值，不会读到写了一半的内容
+    let values = ["ok", "degraded", "down", "maintenance"];
+    let vstring = AtomicString::new("ok".to_string());
+
+    // 前 4 个线程写，后 4 个线程读
+    let report = StressRun::new().threads(8).iterations(1000).run(|w| {
+        if w.index() < 4 {
+            vstring.update_str(w.rng().pick::<&str>(&values));
+        } else {
+            let mut buf = String::with_capacity(DEFAULT_INLINE_CAPACITY);
+            vstring.get_into(&mut buf);
+            assert!(values.contains(&buf.as_str()), "torn read: {:?}", buf);
+        }
+    });
+    report.assert_ok();
+    assert_eq!(vstring.version(), 4000);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_try_update() {
+    let vstring = AtomicString::new("Initial Message".to_string());
+    assert_eq!(vstring.try_update("first".to_string()), Ok(()));
+    assert_eq!(vstring.get(), "first");
+
+    // 模拟另一个写者正持有写锁
+    let seq = vstring.try_lock().unwrap();
+    assert_eq!(
+        vstring.try_update("second".to_string()),
+        Err("second".to_string())
+    );
+    let start = Instant::now();
+    let res = vstring.try_update_for("third".to_string(), Duration::from_millis(20));
+    assert_eq!(res, Err("third".to_string()));
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    vstring.unlock(seq);
+
+    assert_eq!(
+        vstring.try_update_for("fourth".to_string(), Duration::from_millis(20)),
+        Ok(())
+    );
+    assert_eq!(vstring.get(), "fourth");
+    assert_eq!(vstring.version(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_try_update_for_waits_for_readers() {
+    let vstring = Arc::new(AtomicString::new(
+        "a value that is too long to be stored inline".to_string(),
+    ));
+
+    // 模拟一个一直没有读完旧值的读者
+    let guard = vstring.readers.enter();
+    let vs = vstring.clone();
+    let writer = thread::spawn(move || {
+        vs.try_update_for(
+            "another value that is also too long to be inlined".to_string(),
+            Duration::from_millis(1),
+        )
+    });
+    // 新值已经发布，但写者在等读者离开，超过 timeout 也不会返回
+    while vstring.version() == 0 {
+        thread::yield_now();
+    }
+    thread::sleep(Duration::from_millis(50));
+    assert!(!writer.is_finished());
+    assert_eq!(
+        vstring.get(),
+        "another value that is also too long to be inlined"
+    );
+
+    drop(guard);
+    assert_eq!(writer.join().unwrap(), Ok(()));
+}
+
+#[test]
+fn test_traits() {
+    let vstring = AtomicString::from("ok");
+    vstring.update_str("degraded");
+    assert_eq!(
+        format!("{:?}", vstring),
+        r#"AtomicString { value: "degraded", version: 1 }"#
+    );
+    assert_eq!(vstring.to_string(), "degraded");
+
+    let copy = vstring.clone();
+    vstring.update_str("down");
+    assert_eq!(copy.get(), "degraded"); // 深拷贝，不受原值后续更新的影响
+    assert_eq!(copy.version(), 0);
+
+    let json = serde_json::to_string(&vstring).unwrap();
+    assert_eq!(json, r#""down""#);
+    let back: AtomicString = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.get(), "down");
+
+    assert_eq!(AtomicString::default().get(), "");
+    assert_eq!(AtomicString::from(String::from("x")).get(), "x");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_heap_concurrent() {
+    // 并发读写长值：写者替换后要等读者离开才释放旧值，读者不会读到已释放的内存
+    let values = [
+        "a value that is too long to be stored inline",
+        "another value that is also too long to be inlined",
+    ];
+    let vstring = Arc::new(AtomicString::new(values[0].to_string()));
+
+    let writers: Vec<_> = (0..2)
+        .map(|i| {
+            let vs = vstring.clone();
+            thread::spawn(move || {
+                for n in 0..1000 {
+                    vs.update_str(values[(i + n) % values.len()]);
+                }
+            })
+        })
+        .collect();
+    let readers: Vec<_> = (0..4)
+        .map(|_| {
+            let vs = vstring.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    let s = vs.get();
+                    assert!(values.contains(&s.as_str()), "bad read: {:?}", s);
+                }
+            })
+        })
+        .collect();
+
+    for handle in writers.into_iter().chain(readers) {
+        handle.join().unwrap();
+    }
+    assert_eq!(vstring.version(), 2000);
+}
+
+// 统计当前线程的堆分配次数，用来验证短值读写不分配内存
+#[cfg(test)]
+mod alloc_counter {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    thread_local! {
+        static ALLOCS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    struct CountingAlloc;
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    pub fn count<F: FnOnce()>(f: F) -> usize {
+        let before = ALLOCS.with(|n| n.get());
+        f();
+        ALLOCS.with(|n| n.get()) - before
+    }
+}
+
+#[test]
+fn test_inline_no_alloc() {
+    let vstring = AtomicString::new("ok".to_string());
+    let mut buf = String::with_capacity(DEFAULT_INLINE_CAPACITY);
+
+    let allocs = alloc_counter::count(|| {
+        for _ in 0..100 {
+            vstring.update_str("degraded");
+            vstring.get_into(&mut buf);
+            vstring.update_str("ok");
+            vstring.get_into(&mut buf);
+        }
+    });
+    assert_eq!(allocs, 0);
+    assert_eq!(buf, "ok");
+
+    // 关闭内联后每次更新都要分配
+    let heap_only = AtomicString::with_inline_capacity("ok".to_string(), 0);
+    let allocs = alloc_counter::count(|| heap_only.update_str("degraded"));
+    assert!(allocs > 0);
+}
+
+// 基准测试：cargo test --release bench_inline -- --ignored --nocapture
+#[test]
+#[ignore]
+fn bench_inline() {
+    const ROUNDS: u32 = 1_000_000;
+    let values = ["ok", "degraded"];
+
+    for (name, capacity) in [("heap", 0), ("inline", DEFAULT_INLINE_CAPACITY)] {
+        let vstring = AtomicString::with_inline_capacity("ok".to_string(), capacity);
+        let mut buf = String::with_capacity(DEFAULT_INLINE_CAPACITY);
+
+        let mut allocs = 0;
+        let start = std::time::Instant::now();
+        for n in 0..ROUNDS {
+            allocs += alloc_counter::count(|| vstring.update_str(values[(n & 1) as usize]));
+        }
+        let update = start.elapsed() / ROUNDS;
+
+        let start = std::time::Instant::now();
+        for _ in 0..ROUNDS {
+            vstring.get_into(&mut buf);
+        }
+        let read = start.elapsed() / ROUNDS;
+
+        println!(
+            "{:>6}: update {:?}/op, get_into {:?}/op, {} allocs per update",
+            name,
+            update,
+            read,
+            allocs as f64 / ROUNDS as f64
+        );
+    }
+}
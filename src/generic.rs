@@ -71,6 +71,7 @@ impl Summary<usize> for String {
 }
 
 // 为自定义类型实现 `Summary` trait，这里假设我们有某种数据结构需要摘要
+#[allow(dead_code)] // content 只作演示，不参与摘要
 struct NewsArticle {
     headline: String,
     content: String,
@@ -98,6 +99,7 @@ fn test_trait() {
 
 // 自定义一个泛型枚举，表示数学运算的结果
 #[derive(Debug)]
+#[allow(dead_code)] // 字段只通过 {:?} 打印
 enum MathResult<T> {
     Value(T),
     Error(String),
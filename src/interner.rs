@@ -0,0 +1,245 @@
+#[cfg(test)]
+use crate::shim::{thread, Arc};
+use crate::shim::{AtomicPtr, Ordering};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{OnceLock, PoisonError, RwLock};
+
+// 全局字符串驻留表：相同内容的字符串只保存一份，用 Symbol 句柄代替。
+// 驻留的字符串在进程结束前不会释放，适合状态、地区名这类取值有限的字符串
+struct Interner {
+    entries: HashMap<&'static str, &'static Entry>,
+}
+
+// 驻留的字符串及其编号，驻留时泄漏到堆上，此后只读，可以不加锁地访问
+struct Entry {
+    id: u32,
+    s: &'static str,
+}
+
+fn interner() -> &'static RwLock<Interner> {
+    static INTERNER: OnceLock<RwLock<Interner>> = OnceLock::new();
+    INTERNER.get_or_init(|| {
+        RwLock::new(Interner {
+            entries: HashMap::new(),
+        })
+    })
+}
+
+// 驻留字符串的句柄：复制、比较都只是一个指针，取字符串不需要查表加锁
+#[derive(Clone, Copy)]
+pub struct Symbol(&'static Entry);
+
+impl Symbol {
+    // 驻留字符串并返回它的句柄；已经驻留过的直接返回原句柄
+    pub fn intern(s: &str) -> Symbol {
+        // 表只会追加，持锁的线程 panic 也不会留下不一致的状态，锁中毒时照常使用
+        // 绝大多数情况下字符串已经驻留过，只需要读锁
+        let table = interner().read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(&entry) = table.entries.get(s) {
+            return Symbol(entry);
+        }
+        drop(table);
+        let mut table = interner().write().unwrap_or_else(PoisonError::into_inner);
+        // 拿写锁期间可能已被其他线程驻留
+        if let Some(&entry) = table.entries.get(s) {
+            return Symbol(entry);
+        }
+        let id = u32::try_from(table.entries.len()).expect("too many interned strings");
+        let s: &'static str = Box::leak(s.to_owned().into_boxed_str());
+        let entry: &'static Entry = Box::leak(Box::new(Entry { id, s }));
+        table.entries.insert(s, entry);
+        Symbol(entry)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        self.0.s
+    }
+
+    pub fn id(self) -> u32 {
+        self.0.id
+    }
+
+    // AtomicSymbol 中保存的指针；Entry 只读，转成 *mut 只是为了放进 AtomicPtr
+    fn as_ptr(self) -> *mut Entry {
+        self.0 as *const Entry as *mut Entry
+    }
+
+    fn from_ptr(ptr: *mut Entry) -> Symbol {
+        // AtomicSymbol 只保存 as_ptr 得到的指针，指向永不释放的 Entry
+        Symbol(unsafe { &*ptr })
+    }
+}
+
+// 每个字符串只有一个 Entry，按编号比较即可
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.id() == other.id()
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state);
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.id().cmp(&other.id())
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(s: &str) -> Self {
+        Symbol::intern(s)
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Symbol({}, {:?})", self.id(), self.as_str())
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// 保存 Symbol 的原子单元：更新就是替换一个指针，不分配内存也不需要读者计数，
+// 指向的 Entry 永不释放
+pub struct AtomicSymbol {
+    entry: AtomicPtr<Entry>,
+}
+
+impl AtomicSymbol {
+    pub fn new(sym: Symbol) -> Self {
+        AtomicSymbol {
+            entry: AtomicPtr::new(sym.as_ptr()),
+        }
+    }
+
+    pub fn load(&self) -> Symbol {
+        Symbol::from_ptr(self.entry.load(Ordering::Acquire))
+    }
+
+    pub fn store(&self, sym: Symbol) {
+        self.entry.store(sym.as_ptr(), Ordering::Release);
+    }
+
+    // 替换为新值，返回旧值
+    pub fn swap(&self, sym: Symbol) -> Symbol {
+        Symbol::from_ptr(self.entry.swap(sym.as_ptr(), Ordering::AcqRel))
+    }
+
+    // 当前值等于 current 时才替换为 new；失败时返回实际的当前值
+    pub fn compare_exchange(&self, current: Symbol, new: Symbol) -> Result<Symbol, Symbol> {
+        self.entry
+            .compare_exchange(
+                current.as_ptr(),
+                new.as_ptr(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            )
+            .map(Symbol::from_ptr)
+            .map_err(Symbol::from_ptr)
+    }
+
+    // 与 AtomicString 的 update / get 对应的便捷方法
+    pub fn update(&self, s: &str) {
+        self.store(Symbol::intern(s));
+    }
+
+    pub fn get(&self) -> &'static str {
+        self.load().as_str()
+    }
+}
+
+impl fmt::Debug for AtomicSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AtomicSymbol").field(&self.load()).finish()
+    }
+}
+
+impl From<Symbol> for AtomicSymbol {
+    fn from(sym: Symbol) -> Self {
+        AtomicSymbol::new(sym)
+    }
+}
+
+impl From<&str> for AtomicSymbol {
+    fn from(s: &str) -> Self {
+        AtomicSymbol::new(Symbol::intern(s))
+    }
+}
+
+#[test]
+fn test_intern() {
+    let ok = Symbol::intern("ok");
+    let degraded = Symbol::intern("degraded");
+    assert_ne!(ok, degraded);
+    assert_eq!(Symbol::intern("ok"), ok); // 相同内容得到相同编号
+    assert_eq!(Symbol::from("degraded"), degraded);
+    assert_eq!(ok.as_str(), "ok");
+    assert_eq!(degraded.to_string(), "degraded");
+    assert_eq!(format!("{:?}", ok), format!("Symbol({}, \"ok\")", ok.id()));
+}
+
+// 持锁线程 panic 使锁中毒后，驻留和取字符串都照常工作
+#[test]
+fn test_poisoned_lock() {
+    let before = Symbol::intern("before poison");
+    let _ = thread::spawn(|| {
+        let _table = interner().write().unwrap_or_else(PoisonError::into_inner);
+        panic!("poison the interner");
+    })
+    .join();
+    assert!(interner().is_poisoned());
+    assert_eq!(before.as_str(), "before poison");
+    assert_eq!(Symbol::intern("before poison"), before);
+    assert_eq!(Symbol::intern("after poison").as_str(), "after poison");
+}
+
+#[test]
+fn test_atomic_symbol() {
+    let state = Arc::new(AtomicSymbol::from("ok"));
+    let values = ["ok", "degraded", "down"];
+
+    let handles: Vec<_> = (0..10)
+        .map(|i| {
+            let state = state.clone();
+            thread::spawn(move || {
+                for n in 0..100 {
+                    state.update(values[(i + n) % values.len()]);
+                    assert!(values.contains(&state.get()));
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let current = state.load();
+    assert_eq!(state.swap(Symbol::intern("ok")), current);
+    assert_eq!(
+        state.compare_exchange(Symbol::intern("down"), Symbol::intern("degraded")),
+        Err(Symbol::intern("ok"))
+    );
+    assert_eq!(
+        state.compare_exchange(Symbol::intern("ok"), Symbol::intern("degraded")),
+        Ok(Symbol::intern("ok"))
+    );
+    assert_eq!(state.get(), "degraded");
+}
@@ -1,8 +1,25 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 mod atomic1;
-mod library;
+#[cfg(feature = "std")]
 mod atomic2;
 mod atomic3;
-pub mod AtomicString;
+mod atomic_string;
+#[cfg(feature = "std")]
+mod interner;
+#[cfg(feature = "std")]
+pub mod library;
+mod raw;
+mod shim;
+#[cfg(any(feature = "std", test))]
+pub mod stress;
+pub mod sync;
+// 以下为教程示例模块，只包含演示代码和测试
+#[cfg(test)]
 mod smarttest;
+#[cfg(test)]
 mod closure;
-mod generic;
\ No newline at end of file
+#[cfg(test)]
+mod generic;
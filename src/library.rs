@@ -3,108 +3,545 @@ use chrono::NaiveDate;
 // // 由于示例中未直接使用RefCell，此行注释掉了
 // use std::cell::RefCell;
 // 引入Rc，用于实现引用计数的智能指针
+use author::primary_author;
+use catalog::Catalog;
+use std::collections::HashMap;
 use std::rc::Rc;
+use std::{error, fmt};
+
+mod analytics;
+mod author;
+mod catalog;
+mod copy;
+mod hold;
+mod isbn;
+mod key;
+mod loan;
+mod member;
+mod metadata;
+mod persist;
+mod policy;
+mod report;
+mod spreadsheet;
+
+pub use analytics::{
+    AuthorCount, Circulation, DateRange, LoanDuration, MonthCount, OutCount, TitleCount,
+};
+pub use author::{AuthorId, AuthorRegistry, Contributor, Role};
+pub use catalog::{SearchHit, Suggestion, SUGGESTION_LIMIT};
+pub use copy::{BookCopy, CopyStatus};
+pub use hold::{Hold, HoldId, HoldStatus, DEFAULT_PICKUP_DAYS};
+pub use isbn::{Isbn, IsbnError};
+pub use key::{BookId, BookKey};
+pub use loan::{LoanId, Renewal, RenewalPolicy};
+pub use member::{Member, MemberId, MemberStatus};
+pub use metadata::{BookBuilder, BookFilter, BookMetadata};
+pub use persist::{PersistError, SCHEMA_VERSION};
+pub use policy::{FinePolicy, LoanPolicy, DEFAULT_CATEGORY};
+pub use report::{
+    BookRow, HtmlRenderer, JsonRenderer, LoanRow, MarkdownRenderer, Report, ReportRenderer,
+    Summary, TextRenderer,
+};
+pub use spreadsheet::{BookColumns, CsvError, CsvImport, RowError, RowErrorKind};
+
+// 图书馆操作可能出现的错误，调用方可以据此区分各种结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LibraryError {
+    // 图书馆里没有这个编号、ISBN 或书名的书。按书名查找时附带相近的书名，
+    // 按相似度从高到低排列，最多 SUGGESTION_LIMIT 个
+    NotFound {
+        key: String,
+        suggestions: Vec<Suggestion>,
+    },
+    // 有多种书同名，需要用编号或 ISBN 指定
+    AmbiguousTitle {
+        title: String,
+        count: usize,
+    },
+    // ISBN 已被其他书使用
+    DuplicateIsbn {
+        isbn: Isbn,
+    },
+    // 没有这个编号的作者
+    UnknownAuthor {
+        id: AuthorId,
+    },
+    // 书籍至少要有一位作者、编者或译者
+    NoContributors {
+        title: String,
+    },
+    // 这本副本已被借出，尚未归还
+    AlreadyBorrowed {
+        title: String,
+        barcode: String,
+        borrowed_date: NaiveDate,
+    },
+    // 这种书没有可借的副本（全部借出、遗失或在修）
+    NoCopyAvailable {
+        title: String,
+    },
+    // 副本遗失或在修，不能借出
+    CopyUnavailable {
+        barcode: String,
+        status: CopyStatus,
+    },
+    // 没有这个条码的副本
+    UnknownBarcode {
+        barcode: String,
+    },
+    // 条码已被其他副本使用
+    DuplicateBarcode {
+        barcode: String,
+    },
+    // 不允许的副本状态变更，例如直接把副本标记为借出
+    InvalidStatusChange {
+        barcode: String,
+        from: CopyStatus,
+        to: CopyStatus,
+    },
+    // 没有这个编号的读者
+    UnknownMember {
+        id: MemberId,
+    },
+    // 读者已被停借
+    MemberSuspended {
+        id: MemberId,
+    },
+    // 还有可借的副本，不需要预约
+    HoldNotNeeded {
+        title: String,
+    },
+    // 读者已经预约了这种书
+    DuplicateHold {
+        id: MemberId,
+        title: String,
+    },
+    // 没有这个编号的预约
+    UnknownHold {
+        id: HoldId,
+    },
+    // 预约已经完成、取消或过期
+    HoldClosed {
+        id: HoldId,
+        status: HoldStatus,
+    },
+    // 没有这个编号的借阅
+    UnknownLoan {
+        id: LoanId,
+    },
+    // 借阅已经归还
+    LoanClosed {
+        id: LoanId,
+    },
+    // 续借次数已达上限
+    RenewalLimit {
+        id: LoanId,
+        max: u32,
+    },
+    // 有其他读者在排队预约，不能续借
+    HoldPending {
+        title: String,
+    },
+    // 已经逾期，按规则不能续借
+    RenewOverdue {
+        id: LoanId,
+        due_date: NaiveDate,
+    },
+    // 书没有被借出，无法归还
+    NotBorrowed {
+        title: String,
+    },
+    // 日期字符串不是 %Y-%m-%d 格式或不是合法日期
+    InvalidDate {
+        input: String,
+        source: chrono::ParseError,
+    },
+    // 日期加上借期后超出可表示的日期范围
+    DateOutOfRange {
+        date: NaiveDate,
+        days: u32,
+    },
+    // 还书日期早于借书日期
+    ReturnBeforeBorrow {
+        title: String,
+        borrowed_date: NaiveDate,
+        return_date: NaiveDate,
+    },
+}
+
+impl fmt::Display for LibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LibraryError::NotFound { key, suggestions } => {
+                write!(f, "book '{}' not found", key)?;
+                for (i, suggestion) in suggestions.iter().enumerate() {
+                    let sep = if i == 0 { "; did you mean" } else { " or" };
+                    write!(f, "{} '{}'", sep, suggestion.title)?;
+                }
+                if !suggestions.is_empty() {
+                    f.write_str("?")?;
+                }
+                Ok(())
+            }
+            LibraryError::AmbiguousTitle { title, count } => {
+                write!(f, "{} books are titled '{}'", count, title)
+            }
+            LibraryError::DuplicateIsbn { isbn } => {
+                write!(f, "ISBN {} is already in use", isbn)
+            }
+            LibraryError::UnknownAuthor { id } => write!(f, "no author with id {}", id),
+            LibraryError::NoContributors { title } => {
+                write!(f, "book '{}' has no contributors", title)
+            }
+            LibraryError::AlreadyBorrowed {
+                title,
+                barcode,
+                borrowed_date,
+            } => write!(
+                f,
+                "copy {} of '{}' already borrowed on {}",
+                barcode, title, borrowed_date
+            ),
+            LibraryError::NoCopyAvailable { title } => {
+                write!(f, "no copy of '{}' is available", title)
+            }
+            LibraryError::CopyUnavailable { barcode, status } => {
+                write!(f, "copy {} is {}", barcode, status)
+            }
+            LibraryError::UnknownBarcode { barcode } => {
+                write!(f, "no copy with barcode {}", barcode)
+            }
+            LibraryError::DuplicateBarcode { barcode } => {
+                write!(f, "barcode {} is already in use", barcode)
+            }
+            LibraryError::InvalidStatusChange { barcode, from, to } => {
+                write!(f, "copy {} cannot change from {} to {}", barcode, from, to)
+            }
+            LibraryError::UnknownMember { id } => write!(f, "no member with id {}", id),
+            LibraryError::MemberSuspended { id } => write!(f, "member {} is suspended", id),
+            LibraryError::HoldNotNeeded { title } => {
+                write!(f, "a copy of '{}' is available, no hold needed", title)
+            }
+            LibraryError::DuplicateHold { id, title } => {
+                write!(f, "member {} already has a hold on '{}'", id, title)
+            }
+            LibraryError::UnknownHold { id } => write!(f, "no hold with id {}", id),
+            LibraryError::HoldClosed { id, status } => write!(f, "hold {} is {}", id, status),
+            LibraryError::UnknownLoan { id } => write!(f, "no loan with id {}", id),
+            LibraryError::LoanClosed { id } => write!(f, "loan {} has been returned", id),
+            LibraryError::RenewalLimit { id, max } => {
+                write!(f, "loan {} has reached the limit of {} renewals", id, max)
+            }
+            LibraryError::HoldPending { title } => {
+                write!(f, "'{}' is on hold for another member", title)
+            }
+            LibraryError::RenewOverdue { id, due_date } => {
+                write!(f, "loan {} has been overdue since {}", id, due_date)
+            }
+            LibraryError::NotBorrowed { title } => write!(f, "book '{}' is not borrowed", title),
+            LibraryError::InvalidDate { input, source } => {
+                write!(f, "invalid date '{}': {}", input, source)
+            }
+            LibraryError::DateOutOfRange { date, days } => {
+                write!(f, "{} days after {} is out of range", days, date)
+            }
+            LibraryError::ReturnBeforeBorrow {
+                title,
+                borrowed_date,
+                return_date,
+            } => write!(
+                f,
+                "book '{}' returned on {} before it was borrowed on {}",
+                title, return_date, borrowed_date
+            ),
+        }
+    }
+}
+
+impl error::Error for LibraryError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            LibraryError::InvalidDate { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+// 按 %Y-%m-%d 解析日期字符串
+fn parse_date(input: &str) -> Result<NaiveDate, LibraryError> {
+    NaiveDate::parse_from_str(input, "%Y-%m-%d").map_err(|source| LibraryError::InvalidDate {
+        input: input.to_string(),
+        source,
+    })
+}
 
 // 定义书籍结构体，包含书名和共享的作者实例
 #[derive(Debug)] // 使得结构体可以使用 {:?} 格式化为调试信息
-struct Book {
-    title: String,      // 书名，类型为String
-    author: Rc<Author>, // 使用Rc智能指针共享作者实例
+pub struct Book {
+    pub id: BookId,                     // 图书馆内部编号，入藏时分配
+    pub isbn: Option<Isbn>,             // ISBN，没有的书为 None
+    pub title: String,                  // 书名，类型为String
+    pub author: Rc<Author>,             // 主要作者，使用Rc智能指针共享登记的作者实例
+    pub contributors: Vec<Contributor>, // 全部作者、编者和译者，按署名顺序排列
+    pub category: String,               // 分类，决定借期
+    pub metadata: BookMetadata,         // 出版社、出版年份、语种等书目信息
 }
 
 // 定义作者结构体，包含姓名和出生年份
 #[derive(Debug)] // 同样使得结构体支持调试格式化
-struct Author {
-    name: String,    // 作者的名字
-    birth_year: i32, // 作者的出生年份
+pub struct Author {
+    pub name: String,    // 作者的名字
+    pub birth_year: i32, // 作者的出生年份
 }
 
 // 定义借阅记录结构体，记录借阅的书籍和日期
 #[derive(Debug)] // 支持调试输出
-struct BorrowRecord {
-    book: Rc<Book>,                 // 借阅的书籍，通过Rc共享
-    borrowed_date: NaiveDate,       // 借书日期，使用chrono库的NaiveDate类型
-    return_date: Option<NaiveDate>, // 还书日期，可选，使用Option来表示可能无值
+pub struct BorrowRecord {
+    pub id: LoanId,                     // 借阅编号
+    pub book: Rc<Book>,                 // 借阅的书籍，通过Rc共享
+    pub barcode: String,                // 借出的副本条码
+    pub borrower: MemberId,             // 借书的读者
+    pub borrowed_date: NaiveDate,       // 借书日期，使用chrono库的NaiveDate类型
+    pub due_date: NaiveDate,            // 应还日期，借书时按分类的借期算出
+    pub return_date: Option<NaiveDate>, // 还书日期，可选，使用Option来表示可能无值
+    pub fine: u64,                      // 还书时算出的逾期罚款（分），未还或未逾期为 0
+    pub renewals: Vec<Renewal>,         // 续借历史，按续借的先后排列
 }
 
-// 定义图书馆结构体，包含书籍列表和借阅记录列表
-struct Library {
+// 定义图书馆结构体，包含书籍列表、馆藏副本和借阅记录列表
+#[derive(Debug)]
+pub struct Library {
     books: Vec<Rc<Book>>,              // 使用Rc共享的书籍列表
+    authors: AuthorRegistry,           // 登记的作者
+    by_id: HashMap<BookId, Rc<Book>>,  // 按编号索引的书籍
+    by_isbn: HashMap<Isbn, Rc<Book>>,  // 按 ISBN 索引的书籍
+    catalog: Catalog,                  // 书名、作者和关键词的检索索引
+    copies: Vec<BookCopy>,             // 馆藏的实体副本，每种书可以有多本
+    members: Vec<Member>,              // 登记的读者
     borrow_records: Vec<BorrowRecord>, // 借阅记录列表
+    holds: Vec<Hold>,                  // 预约，按预约的先后排列
+    pickup_days: u32,                  // 预约的取书期限
+    loan_policy: LoanPolicy,           // 借期规则
+    fine_policy: FinePolicy,           // 罚款规则
+    renewal_policy: RenewalPolicy,     // 续借规则
+    last_ids: LastIds,                 // 已分配过的最大编号
+}
+
+// 各类实体已分配过的最大编号，新编号直接在此基础上加一，不必扫描全部记录；读档时按存档中的编号恢复
+#[derive(Debug, Default)]
+struct LastIds {
+    book: u32,
+    member: u32,
+    hold: u32,
+    loan: u32,
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        Library::new()
+    }
 }
 
 impl Library {
     // 构造方法，创建一个新的图书馆实例
-    fn new() -> Self {
+    pub fn new() -> Self {
         Library {
-            books: Vec::new(),          // 初始化空的书籍列表
+            books: Vec::new(), // 初始化空的书籍列表
+            authors: AuthorRegistry::default(),
+            by_id: HashMap::new(),
+            by_isbn: HashMap::new(),
+            catalog: Catalog::default(),
+            copies: Vec::new(),         // 初始化空的副本列表
+            members: Vec::new(),        // 初始化空的读者列表
             borrow_records: Vec::new(), // 初始化空的借阅记录列表
+            holds: Vec::new(),          // 初始化空的预约列表
+            pickup_days: DEFAULT_PICKUP_DAYS,
+            loan_policy: LoanPolicy::default(),
+            fine_policy: FinePolicy::default(),
+            renewal_policy: RenewalPolicy::default(),
+            last_ids: LastIds::default(),
         }
     }
 
-    // 添加书籍到图书馆，传入书名和作者实例
-    fn add_book(&mut self, title: String, author: Rc<Author>) {
-        let book = Rc::new(Book { title, author }); // 创建书籍实例，并用Rc包装以便共享
-        self.books.push(book); // 将书籍添加到书籍列表
+    // 添加书籍到图书馆，传入书名和作者实例，返回新加入的书籍。作者会被登记，
+    // 已登记过同一人时书籍使用登记的实例。同时入藏一本自动编号的副本，更多副本用 add_copy 添加。
+    // 书籍无法加入时（例如编号冲突）返回错误
+    pub fn add_book(
+        &mut self,
+        title: String,
+        author: Rc<Author>,
+    ) -> Result<Rc<Book>, LibraryError> {
+        self.add_book_in_category(title, author, DEFAULT_CATEGORY)
     }
 
-    // 借阅书籍，根据书名和借书日期字符串进行操作
-    fn borrow_book(&mut self, book_title: &str, borrow_date_str: &str) {
-        if let Some(book) = self.books.iter().find(|book| book.title == book_title) {
-            // 查找指定标题的书籍
-            let borrowed_date = NaiveDate::parse_from_str(borrow_date_str, "%Y-%m-%d")
-                .expect("Failed to parse date"); // 解析借书日期字符串为NaiveDate
-            let record = BorrowRecord {
-                book: Rc::clone(book), // 克隆书籍的Rc引用，不增加原始引用的计数
-                borrowed_date,         // 使用解析得到的借书日期
-                return_date: None,     // 初始设置还书日期为None
-            };
-            self.borrow_records.push(record); // 将借阅记录添加到记录列表
-            println!("Book '{}' borrowed on {}", book_title, borrow_date_str); // 打印借书信息
-        } else {
-            println!("Book not found."); // 如果没有找到书，则打印提示信息
-        }
+    // 添加指定分类的书籍，其余同 add_book
+    pub fn add_book_in_category(
+        &mut self,
+        title: String,
+        author: Rc<Author>,
+        category: impl Into<String>,
+    ) -> Result<Rc<Book>, LibraryError> {
+        let contributors = self.sole_author(author);
+        let metadata = BookMetadata::default();
+        self.insert_book(title, contributors, category.into(), None, metadata)
+    }
+
+    // 添加带 ISBN 的书籍，ISBN 已被其他书使用时失败，其余同 add_book
+    pub fn add_book_with_isbn(
+        &mut self,
+        title: String,
+        author: Rc<Author>,
+        isbn: Isbn,
+    ) -> Result<Rc<Book>, LibraryError> {
+        let contributors = self.sole_author(author);
+        self.insert_book(
+            title,
+            contributors,
+            DEFAULT_CATEGORY.to_string(),
+            Some(isbn),
+            BookMetadata::default(),
+        )
+    }
+
+    // 登记作者，作为书籍唯一的著者
+    fn sole_author(&mut self, author: Rc<Author>) -> Vec<Contributor> {
+        let (_, author) = self.authors.intern(author);
+        vec![Contributor {
+            author,
+            role: Role::Author,
+        }]
+    }
+
+    // contributors 中的作者都已登记且至少有一位
+    fn insert_book(
+        &mut self,
+        title: String,
+        contributors: Vec<Contributor>,
+        category: String,
+        isbn: Option<Isbn>,
+        metadata: BookMetadata,
+    ) -> Result<Rc<Book>, LibraryError> {
+        let book = self.push_book(Book {
+            id: self.next_book_id(),
+            isbn,
+            title,
+            author: primary_author(&contributors),
+            contributors,
+            category,
+            metadata,
+        })?; // 创建书籍实例，用Rc包装后加入书籍列表和索引
+        let barcode = self.next_barcode();
+        self.copies.push(BookCopy {
+            barcode,
+            book: Rc::clone(&book),
+            status: CopyStatus::Available,
+        });
+        Ok(book)
     }
 
-    // 归还书籍，根据书名和还书日期字符串更新记录
-    fn return_book(&mut self, book_title: &str, return_date_str: &str) {
-        if let Some(record) = self
+    // 借阅书籍，根据读者、书籍（编号、ISBN 或书名）和借书日期字符串进行操作，自动分配一本可借的副本，成功时返回新的借阅记录。
+    // 读者预约的副本已留出时借走留给他的那本
+    pub fn borrow_book<'a>(
+        &mut self,
+        member: MemberId,
+        book: impl Into<BookKey<'a>>,
+        borrow_date_str: &str,
+    ) -> Result<&BorrowRecord, LibraryError> {
+        self.check_borrower(member)?; // 读者必须已登记且没有被停借
+        let book = self.find_book(book)?; // 查找指定的书籍
+        let borrowed_date = parse_date(borrow_date_str)?; // 解析借书日期字符串为NaiveDate
+        let due_date = self.loan_policy.due_date(&book.category, borrowed_date)?;
+        let index = match self.ready_hold(member, &book) {
+            Some(hold) => self.collect_hold(hold),
+            None => self
+                .copies
+                .iter()
+                .position(|copy| {
+                    Rc::ptr_eq(&copy.book, &book) && copy.status == CopyStatus::Available
+                })
+                .ok_or_else(|| LibraryError::NoCopyAvailable {
+                    title: book.title.clone(),
+                })?,
+        };
+        Ok(self.lend(index, member, borrowed_date, due_date))
+    }
+
+    // 归还书籍，根据书籍和还书日期字符串更新记录，成功时返回更新后的借阅记录。
+    // 同一种书借出了多本时归还最早借出的那本，需要指定副本时用 return_copy
+    pub fn return_book<'a>(
+        &mut self,
+        book: impl Into<BookKey<'a>>,
+        return_date_str: &str,
+    ) -> Result<&BorrowRecord, LibraryError> {
+        let book = self.find_book(book)?;
+        let return_date = parse_date(return_date_str)?; // 解析还书日期字符串为NaiveDate
+
+        // 查找对应书籍且尚未归还的记录
+        let index = self
             .borrow_records
-            .iter_mut()
-            .find(|record| record.book.title == book_title && record.return_date.is_none())
-        {
-            // 查找对应书名且尚未归还的记录
-            let return_date = NaiveDate::parse_from_str(return_date_str, "%Y-%m-%d")
-                .expect("Failed to parse date"); // 解析还书日期字符串为NaiveDate
-            record.return_date = Some(return_date); // 更新记录的还书日期
-            println!("Book '{}' returned on {}", book_title, return_date_str); // 打印还书信息
-        } else {
-            println!("Book not found or already returned."); // 如果书未找到或已归还，则打印提示信息
-        }
+            .iter()
+            .position(|record| Rc::ptr_eq(&record.book, &book) && record.return_date.is_none())
+            .ok_or_else(|| LibraryError::NotBorrowed {
+                title: book.title.clone(),
+            })?;
+        self.close(index, return_date)
+    }
+
+    // 把第 index 本副本借给读者，登记借阅记录。应还日期由调用方事先算好，
+    // 算不出来时在改动任何状态之前就已失败
+    fn lend(
+        &mut self,
+        index: usize,
+        borrower: MemberId,
+        borrowed_date: NaiveDate,
+        due_date: NaiveDate,
+    ) -> &BorrowRecord {
+        let id = self.next_loan_id();
+        let copy = &mut self.copies[index];
+        copy.status = CopyStatus::OnLoan;
+        let record = BorrowRecord {
+            id,
+            book: Rc::clone(&copy.book), // 借阅的书籍，与书籍列表共享同一个实例
+            barcode: copy.barcode.clone(),
+            borrower,
+            borrowed_date,     // 使用解析得到的借书日期
+            due_date,          // 按借期规则算出的应还日期
+            return_date: None, // 初始设置还书日期为None
+            fine: 0,
+            renewals: Vec::new(),
+        };
+        self.borrow_records.push(record); // 将借阅记录添加到记录列表
+        self.last_ids.loan = id.0;
+        self.borrow_records.last().unwrap()
     }
 
-    // 打印图书馆的详细信息，包括书籍列表和借阅记录
-    fn print_library_info(&self) {
-        println!("Books:");
-        for book in &self.books {
-            println!(
-                "Title: {}, Author: {} (Born: {})",
-                book.title, book.author.name, book.author.birth_year
-            ); // 打印书籍信息，包括作者的出生年份
+    // 结束第 index 条借阅记录，副本留给排队预约的读者或重新变为可借
+    fn close(
+        &mut self,
+        index: usize,
+        return_date: NaiveDate,
+    ) -> Result<&BorrowRecord, LibraryError> {
+        let record = &mut self.borrow_records[index];
+        if return_date < record.borrowed_date {
+            return Err(LibraryError::ReturnBeforeBorrow {
+                title: record.book.title.clone(),
+                borrowed_date: record.borrowed_date,
+                return_date,
+            });
         }
-        println!("Borrow Records:");
-        for record in &self.borrow_records {
-            let return_date_str = match record.return_date {
-                Some(date) => date.format("%Y-%m-%d").to_string(), // 格式化还书日期
-                None => "Not returned yet".to_string(),            // 若未归还，则显示此信息
-            };
-            println!(
-                "Title: {}, Borrowed on: {}, Returned on: {}",
-                record.book.title,
-                record.borrowed_date.format("%Y-%m-%d"), // 格式化借书日期
-                return_date_str
-            ); // 打印借阅记录详情
+        record.return_date = Some(return_date); // 更新记录的还书日期
+        record.fine = self.fine_policy.fine(record.due_date, return_date); // 逾期则记下罚款
+        if let Some(copy) = self.copies.iter().position(|c| c.barcode == record.barcode) {
+            self.shelve(copy, return_date);
         }
+        Ok(&self.borrow_records[index])
+    }
+
+    // 以对齐的文本表格打印图书馆的报表，包括书籍、借阅记录和汇总
+    pub fn print_library_info(&self) {
+        let mut stdout = std::io::stdout().lock();
+        // 写到标准输出失败时没有别的地方可以报告
+        let _ = self.write_report(&TextRenderer, &mut stdout);
     }
 }
 
@@ -119,12 +556,96 @@ fn testmain() {
     // 创建图书馆实例
     let mut library = Library::new();
     // 添加书籍到图书馆
-    library.add_book("Rust Programming".into(), Rc::clone(&author));
+    library
+        .add_book("Rust Programming".into(), Rc::clone(&author))
+        .unwrap();
+    // 登记读者
+    let reader = library.register_member("Alice", "alice@example.com").id;
 
     // 模拟借阅书籍
-    library.borrow_book("Rust Programming", "2023-04-01");
+    let record = library
+        .borrow_book(reader, "Rust Programming", "2023-04-01")
+        .unwrap();
+    assert_eq!(record.book.title, "Rust Programming");
+    assert_eq!(record.borrower, reader);
+    assert_eq!(record.return_date, None);
     // 模拟归还书籍
-    library.return_book("Rust Programming", "2023-04-30");
+    let record = library
+        .return_book("Rust Programming", "2023-04-30")
+        .unwrap();
+    assert_eq!(record.return_date, NaiveDate::from_ymd_opt(2023, 4, 30));
     // 打印图书馆的全部信息
     library.print_library_info();
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_errors() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    let reader = library.register_member("Alice", "alice@example.com").id;
+
+    assert_eq!(
+        library
+            .borrow_book(reader, "Go Programming", "2023-04-01")
+            .unwrap_err(),
+        LibraryError::NotFound {
+            key: "Go Programming".into(),
+            suggestions: vec![Suggestion {
+                book: BookId(1),
+                title: "Rust Programming".into(),
+                similarity: 75,
+            }],
+        }
+    );
+    assert_eq!(
+        library
+            .borrow_book(reader, "Rust Programing", "2023-04-01")
+            .unwrap_err()
+            .to_string(),
+        "book 'Rust Programing' not found; did you mean 'Rust Programming'?"
+    );
+    let err = library
+        .borrow_book(reader, "Rust Programming", "2023-02-30")
+        .unwrap_err();
+    assert!(matches!(err, LibraryError::InvalidDate { ref input, .. } if input == "2023-02-30"));
+    assert!(error::Error::source(&err).is_some());
+    assert_eq!(
+        library
+            .return_book("Rust Programming", "2023-04-30")
+            .unwrap_err(),
+        LibraryError::NotBorrowed {
+            title: "Rust Programming".into()
+        }
+    );
+
+    library
+        .borrow_book(reader, "Rust Programming", "2023-04-01")
+        .unwrap();
+    assert_eq!(
+        library
+            .borrow_book(reader, "Rust Programming", "2023-04-02")
+            .unwrap_err(),
+        LibraryError::NoCopyAvailable {
+            title: "Rust Programming".into(),
+        }
+    );
+    let err = library
+        .return_book("Rust Programming", "2023-03-31")
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "book 'Rust Programming' returned on 2023-03-31 before it was borrowed on 2023-04-01"
+    );
+    // 出错不会改变记录，之后仍可正常归还、再借
+    library
+        .return_book("Rust Programming", "2023-04-01")
+        .unwrap();
+    library
+        .borrow_book(reader, "Rust Programming", "2023-04-02")
+        .unwrap();
+    assert_eq!(library.borrow_records.len(), 2);
+}
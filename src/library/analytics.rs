@@ -0,0 +1,383 @@
+// 流通统计：按借书日期统计一段时间内的借阅。热门书籍和作者、借阅时长、
+// 每月借阅量和按时归还率都只看这段时间内借出的书，当前在借数看的是全部借阅记录
+#[cfg(test)]
+use super::Author;
+use super::{parse_date, AuthorId, BookId, BorrowRecord, Library, LibraryError, Role};
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+use std::collections::HashMap;
+#[cfg(test)]
+use std::rc::Rc;
+
+// 统计的时间段，首尾两天都包含在内
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl DateRange {
+    // 日期格式与 borrow_book 相同
+    pub fn new(from: &str, to: &str) -> Result<DateRange, LibraryError> {
+        Ok(DateRange {
+            from: parse_date(from)?,
+            to: parse_date(to)?,
+        })
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+}
+
+// 一种书被借的次数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TitleCount {
+    pub book: BookId,
+    pub title: String,
+    pub loans: usize,
+}
+
+// 一位作者的书被借的次数
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuthorCount {
+    pub author: AuthorId,
+    pub name: String,
+    pub loans: usize,
+}
+
+// 已归还借阅的借阅天数
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LoanDuration {
+    pub returned: usize, // 参与统计的已归还借阅数
+    pub mean_days: f64,
+    pub median_days: f64,
+}
+
+// 一个月内借出的次数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MonthCount {
+    pub year: i32,
+    pub month: u32,
+    pub loans: usize,
+}
+
+// 某一天仍未归还的借阅数，其中已经逾期的数量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct OutCount {
+    pub loans: usize,
+    pub overdue: usize,
+}
+
+// 一段时间的流通统计，报表渲染器可以直接输出
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Circulation {
+    pub range: DateRange,
+    pub loans: usize, // 这段时间内借出的次数
+    pub top_titles: Vec<TitleCount>,
+    pub top_authors: Vec<AuthorCount>,
+    pub duration: Option<LoanDuration>, // 没有已归还的借阅时为 None
+    pub per_month: Vec<MonthCount>,
+    pub on_time_rate: Option<f64>, // 已归还的借阅中按时归还的比例，0 到 1
+    pub out: OutCount,             // 截至时间段最后一天仍未归还的借阅
+}
+
+// 按次数从多到少排列，次数相同时按名称排列，最多保留 limit 项
+fn top<T>(mut counts: Vec<T>, limit: usize, key: impl Fn(&T) -> (usize, &str)) -> Vec<T> {
+    counts.sort_by(|a, b| {
+        let (a_loans, a_name) = key(a);
+        let (b_loans, b_name) = key(b);
+        b_loans.cmp(&a_loans).then_with(|| a_name.cmp(b_name))
+    });
+    counts.truncate(limit);
+    counts
+}
+
+impl Library {
+    fn loans_in(&self, range: DateRange) -> impl Iterator<Item = &BorrowRecord> {
+        self.borrow_records
+            .iter()
+            .filter(move |record| range.contains(record.borrowed_date))
+    }
+
+    // 借得最多的书，按书籍编号区分同名的书
+    pub fn most_borrowed_titles(&self, range: DateRange, limit: usize) -> Vec<TitleCount> {
+        let mut counts: HashMap<BookId, TitleCount> = HashMap::new();
+        for record in self.loans_in(range) {
+            counts
+                .entry(record.book.id)
+                .or_insert_with(|| TitleCount {
+                    book: record.book.id,
+                    title: record.book.title.clone(),
+                    loans: 0,
+                })
+                .loans += 1;
+        }
+        top(counts.into_values().collect(), limit, |c| {
+            (c.loans, c.title.as_str())
+        })
+    }
+
+    // 著作被借得最多的作者。一本书的每位著者都计一次，编者和译者不计；
+    // 没有著者的书计入它的主要作者
+    pub fn most_borrowed_authors(&self, range: DateRange, limit: usize) -> Vec<AuthorCount> {
+        let mut counts: HashMap<AuthorId, AuthorCount> = HashMap::new();
+        for record in self.loans_in(range) {
+            let book = &record.book;
+            let mut authors: Vec<_> = book
+                .contributors
+                .iter()
+                .filter(|c| c.role == Role::Author)
+                .map(|c| &c.author)
+                .collect();
+            if authors.is_empty() {
+                authors.push(&book.author);
+            }
+            for author in authors {
+                // 书籍的作者都是在登记表里登记过的实例
+                let id = self.authors.id_of_entry(author);
+                debug_assert!(id.is_some(), "author '{}' is not registered", author.name);
+                let Some(id) = id else { continue };
+                counts
+                    .entry(id)
+                    .or_insert_with(|| AuthorCount {
+                        author: id,
+                        name: author.name.clone(),
+                        loans: 0,
+                    })
+                    .loans += 1;
+            }
+        }
+        top(counts.into_values().collect(), limit, |c| {
+            (c.loans, c.name.as_str())
+        })
+    }
+
+    // 这段时间内借出并已归还的借阅，从借书到还书的天数
+    pub fn loan_duration(&self, range: DateRange) -> Option<LoanDuration> {
+        let mut days: Vec<i64> = self
+            .loans_in(range)
+            .filter_map(|record| {
+                let returned = record.return_date?;
+                Some((returned - record.borrowed_date).num_days())
+            })
+            .collect();
+        if days.is_empty() {
+            return None;
+        }
+        days.sort_unstable();
+        let mid = days.len() / 2;
+        let median_days = if days.len() % 2 == 0 {
+            (days[mid - 1] + days[mid]) as f64 / 2.0
+        } else {
+            days[mid] as f64
+        };
+        Some(LoanDuration {
+            returned: days.len(),
+            mean_days: days.iter().sum::<i64>() as f64 / days.len() as f64,
+            median_days,
+        })
+    }
+
+    // 每月借出的次数，包括这段时间内没有借阅的月份
+    pub fn loans_per_month(&self, range: DateRange) -> Vec<MonthCount> {
+        let mut months = Vec::new();
+        let (mut year, mut month) = (range.from.year(), range.from.month());
+        while range.from <= range.to && (year, month) <= (range.to.year(), range.to.month()) {
+            months.push(MonthCount {
+                year,
+                month,
+                loans: 0,
+            });
+            (year, month) = if month == 12 {
+                (year + 1, 1)
+            } else {
+                (year, month + 1)
+            };
+        }
+        // 按与起始月份相差的月数直接找到对应的月份
+        for record in self.loans_in(range) {
+            let date = record.borrowed_date;
+            let offset = (date.year() - range.from.year()) * 12 + date.month() as i32
+                - range.from.month() as i32;
+            months[offset as usize].loans += 1;
+        }
+        months
+    }
+
+    // 已归还的借阅中，在应还日期当天或之前归还的比例；没有已归还的借阅时为 None
+    pub fn on_time_rate(&self, range: DateRange) -> Option<f64> {
+        let (mut returned, mut on_time) = (0, 0);
+        for record in self.loans_in(range) {
+            if let Some(date) = record.return_date {
+                returned += 1;
+                if date <= record.due_date {
+                    on_time += 1;
+                }
+            }
+        }
+        (returned > 0).then(|| on_time as f64 / returned as f64)
+    }
+
+    // 截至 as_of 已借出、还没归还的借阅数，以及其中已经逾期的数量
+    pub fn currently_out(&self, as_of: NaiveDate) -> OutCount {
+        let out = self.borrow_records.iter().filter(|record| {
+            record.borrowed_date <= as_of && record.return_date.map_or(true, |date| date > as_of)
+        });
+        OutCount {
+            loans: out.clone().count(),
+            overdue: out.filter(|record| record.due_date < as_of).count(),
+        }
+    }
+
+    // 这段时间的全部统计，热门书籍和作者各保留 limit 项
+    pub fn circulation(&self, range: DateRange, limit: usize) -> Circulation {
+        Circulation {
+            range,
+            loans: self.loans_in(range).count(),
+            top_titles: self.most_borrowed_titles(range, limit),
+            top_authors: self.most_borrowed_authors(range, limit),
+            duration: self.loan_duration(range),
+            per_month: self.loans_per_month(range),
+            on_time_rate: self.on_time_rate(range),
+            out: self.currently_out(range.to),
+        }
+    }
+}
+
+#[cfg(test)]
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[cfg(test)]
+fn sample_library() -> Library {
+    let klabnik = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let blandy = Rc::new(Author {
+        name: "Jim Blandy".into(),
+        birth_year: 1965,
+    });
+    let mut library = Library::new();
+    library
+        .add_book("The Rust Book".into(), Rc::clone(&klabnik))
+        .unwrap();
+    library
+        .add_book("Rust Programming".into(), klabnik)
+        .unwrap();
+    library.add_book("Programming Rust".into(), blandy).unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    // 借期 21 天
+    let loans = [
+        (alice, "The Rust Book", "2023-01-05", Some("2023-01-15")),
+        (bob, "Programming Rust", "2023-01-20", Some("2023-02-20")),
+        (alice, "The Rust Book", "2023-03-01", Some("2023-03-05")),
+        (bob, "Rust Programming", "2023-03-10", Some("2023-03-30")),
+        (alice, "The Rust Book", "2023-03-20", None),
+        (bob, "Programming Rust", "2023-04-10", None),
+    ];
+    for (member, title, borrowed, returned) in loans {
+        library.borrow_book(member, title, borrowed).unwrap();
+        if let Some(returned) = returned {
+            library.return_book(title, returned).unwrap();
+        }
+    }
+    library
+}
+
+#[test]
+fn test_most_borrowed() {
+    let mut library = sample_library();
+    let range = DateRange::new("2023-01-01", "2023-03-31").unwrap();
+    let titles: Vec<_> = library
+        .most_borrowed_titles(range, 2)
+        .into_iter()
+        .map(|c| (c.title, c.loans))
+        .collect();
+    assert_eq!(
+        titles,
+        [
+            ("The Rust Book".to_string(), 3),
+            ("Programming Rust".to_string(), 1)
+        ]
+    );
+    let authors: Vec<_> = library
+        .most_borrowed_authors(range, 10)
+        .into_iter()
+        .map(|c| (c.name, c.loans))
+        .collect();
+    assert_eq!(
+        authors,
+        [
+            ("Steve Klabnik".to_string(), 4),
+            ("Jim Blandy".to_string(), 1)
+        ]
+    );
+
+    // 译者不算作者
+    let translator = library.register_author("张三", 1975);
+    let blandy = library.register_author("Jim Blandy", 1965);
+    library
+        .add_book_with_contributors(
+            "Rust 程序设计".into(),
+            &[(translator, Role::Translator), (blandy, Role::Author)],
+        )
+        .unwrap();
+    let carol = library.register_member("Carol", "").id;
+    library
+        .borrow_book(carol, "Rust 程序设计", "2023-03-25")
+        .unwrap();
+    let authors = library.most_borrowed_authors(range, 10);
+    assert_eq!(authors.len(), 2);
+    assert_eq!((authors[1].author, authors[1].loans), (blandy, 2));
+}
+
+#[test]
+fn test_circulation() {
+    let library = sample_library();
+    let range = DateRange::new("2023-01-01", "2023-03-31").unwrap();
+    // 已归还的借阅天数为 10、31、4、20
+    assert_eq!(
+        library.loan_duration(range),
+        Some(LoanDuration {
+            returned: 4,
+            mean_days: 16.25,
+            median_days: 15.0,
+        })
+    );
+    let months: Vec<_> = library
+        .loans_per_month(range)
+        .into_iter()
+        .map(|m| (m.month, m.loans))
+        .collect();
+    assert_eq!(months, [(1, 2), (2, 0), (3, 3)]);
+    // 一月二十日借出的书逾期归还
+    assert_eq!(library.on_time_rate(range), Some(0.75));
+
+    let circulation = library.circulation(range, 1);
+    assert_eq!(circulation.loans, 5);
+    assert_eq!(circulation.top_titles.len(), 1);
+    assert_eq!(
+        circulation.out,
+        OutCount {
+            loans: 1,
+            overdue: 0
+        }
+    );
+    assert_eq!(
+        library.currently_out(date("2023-05-01")),
+        OutCount {
+            loans: 2,
+            overdue: 1
+        }
+    );
+
+    let empty = DateRange::new("2022-01-01", "2022-01-31").unwrap();
+    assert_eq!(library.loan_duration(empty), None);
+    assert_eq!(library.on_time_rate(empty), None);
+    assert!(library.most_borrowed_titles(empty, 5).is_empty());
+    assert!(DateRange::new("2023-02-30", "2023-03-01").is_err());
+}
@@ -0,0 +1,283 @@
+// 作者登记：同名（忽略大小写和多余空格）且出生年份相同的作者只登记一次，
+// 分配作者编号，所有书籍共享登记的作者实例。一本书可以有多位作者、编者和译者
+use super::{Author, Book, Library, LibraryError};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::rc::Rc;
+
+// 作者编号，登记时按顺序分配，从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct AuthorId(pub(super) u32);
+
+impl AuthorId {
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for AuthorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "A{:04}", self.0)
+    }
+}
+
+// 对书籍的贡献方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Author,     // 著
+    Editor,     // 编
+    Translator, // 译
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Role::Author => "author",
+            Role::Editor => "editor",
+            Role::Translator => "translator",
+        })
+    }
+}
+
+// 书籍的一位作者、编者或译者
+#[derive(Debug, Clone)]
+pub struct Contributor {
+    pub author: Rc<Author>, // 登记过的作者实例
+    pub role: Role,
+}
+
+#[derive(Debug, Default)]
+pub struct AuthorRegistry {
+    authors: BTreeMap<AuthorId, Rc<Author>>,
+    by_key: HashMap<(String, i32), AuthorId>, // (规范化的姓名, 出生年份) -> 编号
+    by_entry: HashMap<*const Author, AuthorId>, // 登记的实例 -> 编号
+}
+
+// 比较作者姓名时忽略大小写和多余的空格
+fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 书籍的主要作者：第一位著者，没有著者时取第一位贡献者
+pub(super) fn primary_author(contributors: &[Contributor]) -> Rc<Author> {
+    let primary = contributors
+        .iter()
+        .find(|c| c.role == Role::Author)
+        .unwrap_or(&contributors[0]);
+    Rc::clone(&primary.author)
+}
+
+impl AuthorRegistry {
+    pub fn get(&self, id: AuthorId) -> Option<&Rc<Author>> {
+        self.authors.get(&id)
+    }
+
+    // 与 author 是同一人的登记作者的编号
+    pub fn id_of(&self, author: &Author) -> Option<AuthorId> {
+        let key = (normalize(&author.name), author.birth_year);
+        self.by_key.get(&key).copied()
+    }
+
+    // 登记的作者实例的编号。按实例而不是姓名查找，读档得到的同名同年的两位作者也能区分；
+    // author 不是登记过的实例时返回 None
+    pub fn id_of_entry(&self, author: &Rc<Author>) -> Option<AuthorId> {
+        self.by_entry.get(&Rc::as_ptr(author)).copied()
+    }
+
+    // 按编号顺序遍历登记的作者
+    pub fn iter(&self) -> impl Iterator<Item = (AuthorId, &Rc<Author>)> {
+        self.authors.iter().map(|(&id, author)| (id, author))
+    }
+
+    pub fn len(&self) -> usize {
+        self.authors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.authors.is_empty()
+    }
+
+    // 登记作者并返回登记的实例；已有同一人时返回已登记的实例
+    pub(super) fn intern(&mut self, author: Rc<Author>) -> (AuthorId, Rc<Author>) {
+        if let Some(id) = self.id_of(&author) {
+            return (id, Rc::clone(&self.authors[&id]));
+        }
+        let last = self.authors.last_key_value().map_or(0, |(id, _)| id.0);
+        let id = AuthorId(last + 1);
+        self.insert(id, Rc::clone(&author));
+        (id, author)
+    }
+
+    // 以指定编号登记作者，读档时使用；编号已被使用时返回 false
+    pub(super) fn insert(&mut self, id: AuthorId, author: Rc<Author>) -> bool {
+        if self.authors.contains_key(&id) {
+            return false;
+        }
+        let key = (normalize(&author.name), author.birth_year);
+        self.by_key.entry(key).or_insert(id);
+        self.by_entry.insert(Rc::as_ptr(&author), id);
+        self.authors.insert(id, author);
+        true
+    }
+}
+
+impl Library {
+    pub fn authors(&self) -> &AuthorRegistry {
+        &self.authors
+    }
+
+    pub fn author(&self, id: AuthorId) -> Option<&Rc<Author>> {
+        self.authors.get(id)
+    }
+
+    // 登记作者，返回作者编号；已有同名同年的作者时返回已有的编号
+    pub fn register_author(&mut self, name: impl Into<String>, birth_year: i32) -> AuthorId {
+        let author = Rc::new(Author {
+            name: name.into(),
+            birth_year,
+        });
+        self.authors.intern(author).0
+    }
+
+    // 添加有多位作者、编者或译者的书籍，按给出的顺序排列。
+    // 第一位著者作为书籍的主要作者，没有著者时取第一位贡献者
+    pub fn add_book_with_contributors(
+        &mut self,
+        title: String,
+        contributors: &[(AuthorId, Role)],
+    ) -> Result<Rc<Book>, LibraryError> {
+        let contributors = self.contributors(contributors)?;
+        if contributors.is_empty() {
+            return Err(LibraryError::NoContributors { title });
+        }
+        self.insert_book(
+            title,
+            contributors,
+            super::DEFAULT_CATEGORY.to_string(),
+            None,
+            super::BookMetadata::default(),
+        )
+    }
+
+    // 作者以任何身份参与的书籍，按入藏顺序排列
+    pub fn books_by(&self, id: AuthorId) -> Result<Vec<Rc<Book>>, LibraryError> {
+        let author = self.author(id).ok_or(LibraryError::UnknownAuthor { id })?;
+        Ok(self
+            .books
+            .iter()
+            .filter(|book| {
+                book.contributors
+                    .iter()
+                    .any(|c| Rc::ptr_eq(&c.author, author))
+            })
+            .cloned()
+            .collect())
+    }
+
+    // 把编号和身份转换为贡献者列表，编号必须已登记
+    pub(super) fn contributors(
+        &self,
+        contributors: &[(AuthorId, Role)],
+    ) -> Result<Vec<Contributor>, LibraryError> {
+        contributors
+            .iter()
+            .map(|&(id, role)| {
+                let author = self.author(id).ok_or(LibraryError::UnknownAuthor { id })?;
+                Ok(Contributor {
+                    author: Rc::clone(author),
+                    role,
+                })
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test_author_registry() {
+    let mut library = Library::new();
+    let klabnik = library.register_author("Steve Klabnik", 1980);
+    assert_eq!(klabnik.to_string(), "A0001");
+    // 大小写、空格不同的同一人只登记一次，出生年份不同视为不同的人
+    assert_eq!(library.register_author("  steve   KLABNIK ", 1980), klabnik);
+    let namesake = library.register_author("Steve Klabnik", 1990);
+    assert_ne!(namesake, klabnik);
+
+    // 调用方自己创建的作者实例也会合并到登记的实例
+    let ad_hoc = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let book = library.add_book("The Rust Book".into(), ad_hoc).unwrap();
+    assert!(Rc::ptr_eq(&book.author, library.author(klabnik).unwrap()));
+    assert_eq!(library.authors().len(), 2);
+}
+
+#[test]
+fn test_contributors() {
+    let mut library = Library::new();
+    let klabnik = library.register_author("Steve Klabnik", 1980);
+    let nichols = library.register_author("Carol Nichols", 1983);
+    let translator = library.register_author("张三", 1975);
+    let book = library
+        .add_book_with_contributors(
+            "Rust 权威指南".into(),
+            &[
+                (translator, Role::Translator),
+                (klabnik, Role::Author),
+                (nichols, Role::Author),
+            ],
+        )
+        .unwrap();
+    let roles: Vec<_> = book
+        .contributors
+        .iter()
+        .map(|c| (c.author.name.as_str(), c.role))
+        .collect();
+    assert_eq!(
+        roles,
+        [
+            ("张三", Role::Translator),
+            ("Steve Klabnik", Role::Author),
+            ("Carol Nichols", Role::Author),
+        ]
+    );
+    // 主要作者是第一位著者，不是译者
+    assert_eq!(book.author.name, "Steve Klabnik");
+    library
+        .add_book(
+            "The Rust Book".into(),
+            Rc::clone(library.author(klabnik).unwrap()),
+        )
+        .unwrap();
+
+    let titles =
+        |books: Vec<Rc<Book>>| -> Vec<String> { books.iter().map(|b| b.title.clone()).collect() };
+    assert_eq!(
+        titles(library.books_by(klabnik).unwrap()),
+        ["Rust 权威指南", "The Rust Book"]
+    );
+    assert_eq!(
+        titles(library.books_by(translator).unwrap()),
+        ["Rust 权威指南"]
+    );
+    assert_eq!(titles(library.search_author("张")), ["Rust 权威指南"]);
+
+    let stranger = AuthorId(99);
+    assert_eq!(
+        library.books_by(stranger).unwrap_err(),
+        LibraryError::UnknownAuthor { id: stranger }
+    );
+    assert_eq!(
+        library
+            .add_book_with_contributors("Anonymous".into(), &[])
+            .unwrap_err(),
+        LibraryError::NoContributors {
+            title: "Anonymous".into()
+        }
+    );
+}
@@ -0,0 +1,370 @@
+// 馆藏目录索引：书名（不区分大小写，支持前缀）、作者名和书名关键词的倒排索引。
+// 拉丁字母和数字按单词切分，中日韩文字没有空格分词，按单字和相邻两字切分。
+// 书名找不到时按编辑距离给出相近的书名
+#[cfg(test)]
+use super::Author;
+use super::{Book, BookId, Library};
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+// 关键词搜索的一条结果，score 越大越相关
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub book: Rc<Book>,
+    pub score: u32,
+}
+
+// 相近书名的建议，similarity 为 0 到 100 的相似度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    pub book: BookId,
+    pub title: String,
+    pub similarity: u8,
+}
+
+// 找不到书时最多给出的建议数
+pub const SUGGESTION_LIMIT: usize = 3;
+
+// 相似度低于它的书名不作为建议
+const MIN_SIMILARITY: u8 = 50;
+
+#[derive(Debug, Default)]
+pub(super) struct Catalog {
+    titles: BTreeMap<String, BTreeSet<BookId>>, // 小写书名 -> 书籍
+    title_terms: BTreeMap<String, BTreeMap<BookId, u32>>, // 书名中的词 -> 书籍及出现次数
+    author_terms: BTreeMap<String, BTreeSet<BookId>>, // 作者名中的词 -> 书籍
+}
+
+impl Catalog {
+    pub(super) fn insert(&mut self, book: &Book) {
+        self.titles
+            .entry(book.title.to_lowercase())
+            .or_default()
+            .insert(book.id);
+        for term in tokenize(&book.title) {
+            *self
+                .title_terms
+                .entry(term)
+                .or_default()
+                .entry(book.id)
+                .or_default() += 1;
+        }
+        for contributor in &book.contributors {
+            for term in tokenize(&contributor.author.name) {
+                self.author_terms.entry(term).or_default().insert(book.id);
+            }
+        }
+    }
+
+    // 小写后与 title 相同的书名
+    pub(super) fn same_title(&self, title: &str) -> impl Iterator<Item = BookId> + '_ {
+        self.titles
+            .get(&title.to_lowercase())
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+}
+
+// 把文本切分为小写的词：拉丁字母和数字连续的一段为一个词，
+// 中日韩文字每个字是一个词，相邻两个字再组成一个词
+pub(super) fn tokenize(text: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut word = String::new();
+    let mut prev_cjk = None;
+    for ch in text.chars().flat_map(char::to_lowercase) {
+        if is_cjk(ch) {
+            if !word.is_empty() {
+                terms.push(std::mem::take(&mut word));
+            }
+            terms.push(ch.to_string());
+            if let Some(prev) = prev_cjk {
+                terms.push([prev, ch].iter().collect());
+            }
+            prev_cjk = Some(ch);
+            continue;
+        }
+        prev_cjk = None;
+        if ch.is_alphanumeric() {
+            word.push(ch);
+        } else if !word.is_empty() {
+            terms.push(std::mem::take(&mut word));
+        }
+    }
+    if !word.is_empty() {
+        terms.push(word);
+    }
+    terms
+}
+
+// 两个字符串的编辑距离（插入、删除、替换一个字符各算一步）
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+// 按编辑距离算出的相似度，完全相同为 100
+fn similarity(a: &[char], b: &[char]) -> u8 {
+    let len = a.len().max(b.len());
+    if len == 0 {
+        return 100;
+    }
+    (100 * (len - edit_distance(a, b)) / len) as u8
+}
+
+pub(super) fn is_cjk(ch: char) -> bool {
+    matches!(ch,
+        '\u{3040}'..='\u{30ff}' // 平假名、片假名
+        | '\u{3400}'..='\u{4dbf}' // 扩展 A
+        | '\u{4e00}'..='\u{9fff}' // 基本汉字
+        | '\u{ac00}'..='\u{d7af}' // 韩文音节
+        | '\u{f900}'..='\u{faff}' // 兼容汉字
+    )
+}
+
+impl Library {
+    // 按书名搜索，不区分大小写：书名完全相同的排在前面，其次是以 query 开头的，各自按书名排列
+    pub fn search_title(&self, query: &str) -> Vec<Rc<Book>> {
+        let query = query.to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut exact = Vec::new();
+        let mut prefix = Vec::new();
+        for (title, ids) in self.catalog.titles.range(query.clone()..) {
+            if !title.starts_with(&query) {
+                break;
+            }
+            let books = if *title == query {
+                &mut exact
+            } else {
+                &mut prefix
+            };
+            books.extend(ids.iter().map(|id| Rc::clone(&self.by_id[id])));
+        }
+        exact.extend(prefix);
+        exact
+    }
+
+    // 按作者名搜索，query 的每个词都要是作者名中某个词的前缀，
+    // 例如 "steve k" 能找到 Steve Klabnik 的书。结果按作者名、书名排列
+    pub fn search_author(&self, query: &str) -> Vec<Rc<Book>> {
+        let mut found: Option<BTreeSet<BookId>> = None;
+        for term in tokenize(query) {
+            let books: BTreeSet<_> = self
+                .catalog
+                .author_terms
+                .range(term.clone()..)
+                .take_while(|(t, _)| t.starts_with(&term))
+                .flat_map(|(_, ids)| ids.iter().copied())
+                .collect();
+            found = Some(match found {
+                Some(prev) => &prev & &books,
+                None => books,
+            });
+        }
+        let mut books: Vec<_> = found
+            .unwrap_or_default()
+            .iter()
+            .map(|id| Rc::clone(&self.by_id[id]))
+            .collect();
+        books.sort_by(|a, b| (&a.author.name, &a.title).cmp(&(&b.author.name, &b.title)));
+        books
+    }
+
+    // 按书名中的关键词搜索，至少包含一个词的书都会返回，按相关程度排列。
+    // 每命中 query 中的一个词得 10 分，该词在书名中每出现一次再加 1 分，
+    // 书名包含整个 query 时再加 5 分；同分的按书名排列
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let mut terms = tokenize(query);
+        terms.sort();
+        terms.dedup();
+        let mut scores: BTreeMap<BookId, u32> = BTreeMap::new();
+        for term in &terms {
+            for (&id, &count) in self.catalog.title_terms.get(term).into_iter().flatten() {
+                *scores.entry(id).or_default() += 10 + count;
+            }
+        }
+        let phrase = query.trim().to_lowercase();
+        let mut hits: Vec<_> = scores
+            .into_iter()
+            .map(|(id, mut score)| {
+                let book = &self.by_id[&id];
+                if !phrase.is_empty() && book.title.to_lowercase().contains(&phrase) {
+                    score += 5;
+                }
+                SearchHit {
+                    book: Rc::clone(book),
+                    score,
+                }
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.cmp(&a.score).then(a.book.title.cmp(&b.book.title)));
+        hits
+    }
+
+    // 与 query 相近的书名，不区分大小写，按相似度从高到低最多返回 limit 个
+    pub fn suggest_titles(&self, query: &str, limit: usize) -> Vec<Suggestion> {
+        let query: Vec<char> = query.to_lowercase().chars().collect();
+        let mut suggestions: Vec<_> = self
+            .catalog
+            .titles
+            .iter()
+            .filter_map(|(title, ids)| {
+                let title: Vec<char> = title.chars().collect();
+                let similarity = similarity(&query, &title);
+                (similarity >= MIN_SIMILARITY).then_some((ids, similarity))
+            })
+            .flat_map(|(ids, similarity)| {
+                ids.iter().map(move |id| Suggestion {
+                    book: *id,
+                    title: self.by_id[id].title.clone(),
+                    similarity,
+                })
+            })
+            .collect();
+        suggestions.sort_by(|a, b| {
+            b.similarity
+                .cmp(&a.similarity)
+                .then_with(|| a.title.cmp(&b.title))
+        });
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(
+        tokenize("The Rust Programming Language, 2nd Edition"),
+        ["the", "rust", "programming", "language", "2nd", "edition"]
+    );
+    assert_eq!(
+        tokenize("Rust编程之道"),
+        ["rust", "编", "程", "编程", "之", "程之", "道", "之道"]
+    );
+    assert_eq!(
+        tokenize("Rust 入門 3版"),
+        ["rust", "入", "門", "入門", "3", "版"]
+    );
+    assert!(tokenize(" -- ").is_empty());
+}
+
+#[test]
+fn test_search() {
+    let klabnik = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let blandy = Rc::new(Author {
+        name: "Jim Blandy".into(),
+        birth_year: 1965,
+    });
+    let zhang = Rc::new(Author {
+        name: "张汉东".into(),
+        birth_year: 1985,
+    });
+    let mut library = Library::new();
+    library
+        .add_book("The Rust Programming Language".into(), Rc::clone(&klabnik))
+        .unwrap();
+    library
+        .add_book("Rust".into(), Rc::clone(&klabnik))
+        .unwrap();
+    library.add_book("Programming Rust".into(), blandy).unwrap();
+    library
+        .add_book("Rust编程之道".into(), Rc::clone(&zhang))
+        .unwrap();
+    library.add_book("程序设计".into(), zhang).unwrap();
+
+    let titles =
+        |books: Vec<Rc<Book>>| -> Vec<String> { books.iter().map(|b| b.title.clone()).collect() };
+    // 书名：完全匹配在前，前缀匹配在后
+    assert_eq!(
+        titles(library.search_title("RUST")),
+        ["Rust", "Rust编程之道"]
+    );
+    assert_eq!(
+        titles(library.search_title("the rust")),
+        ["The Rust Programming Language"]
+    );
+    assert!(library.search_title("").is_empty());
+
+    // 作者：共享同一个作者实例的书都能找到，支持前缀
+    assert_eq!(
+        titles(library.search_author("klabnik")),
+        ["Rust", "The Rust Programming Language"]
+    );
+    assert_eq!(titles(library.search_author("st kla")).len(), 2);
+    assert_eq!(
+        titles(library.search_author("汉东")),
+        ["Rust编程之道", "程序设计"]
+    );
+    assert!(library.search_author("steve blandy").is_empty());
+
+    // 关键词：命中的词越多越靠前，包含整个查询的书名再加分
+    let hits = library.search("rust programming");
+    let ranked: Vec<_> = hits
+        .iter()
+        .map(|hit| (hit.book.title.as_str(), hit.score))
+        .collect();
+    assert_eq!(
+        ranked,
+        [
+            ("The Rust Programming Language", 27),
+            ("Programming Rust", 22),
+            ("Rust", 11),
+            ("Rust编程之道", 11),
+        ]
+    );
+    let hits = library.search("编程");
+    assert_eq!(hits[0].book.title, "Rust编程之道");
+    assert_eq!(hits[0].score, 38);
+    assert_eq!(hits[1].book.title, "程序设计");
+    assert!(library.search("python").is_empty());
+}
+
+#[test]
+fn test_suggest_titles() {
+    let chars = |s: &str| -> Vec<char> { s.chars().collect() };
+    assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+    assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+    assert_eq!(edit_distance(&chars("编程之道"), &chars("编程之美")), 1);
+
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library
+        .add_book("Rust Programming".into(), Rc::clone(&author))
+        .unwrap();
+    library
+        .add_book("Rust Programmer".into(), Rc::clone(&author))
+        .unwrap();
+    library.add_book("Go in Action".into(), author).unwrap();
+
+    let suggestions = library.suggest_titles("rust programing", SUGGESTION_LIMIT);
+    let found: Vec<_> = suggestions
+        .iter()
+        .map(|s| (s.title.as_str(), s.similarity))
+        .collect();
+    assert_eq!(found, [("Rust Programming", 93), ("Rust Programmer", 80)]);
+    assert_eq!(
+        suggestions[0].book,
+        library.book("Rust Programming").unwrap().id
+    );
+    assert_eq!(library.suggest_titles("rust programing", 1).len(), 1);
+    assert!(library
+        .suggest_titles("Haskell", SUGGESTION_LIMIT)
+        .is_empty());
+}
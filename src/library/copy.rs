@@ -0,0 +1,363 @@
+// 实体副本：同一种书可以有多本，每本用条码区分，借还的都是具体的某一本
+#[cfg(test)]
+use super::Author;
+use super::{parse_date, Book, BookKey, BorrowRecord, Library, LibraryError, MemberId};
+#[cfg(test)]
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::rc::Rc;
+
+// 副本的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyStatus {
+    Available, // 在架可借
+    OnLoan,    // 已借出
+    Lost,      // 遗失
+    InRepair,  // 修补中
+    OnHold,    // 留给预约的读者
+}
+
+impl fmt::Display for CopyStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CopyStatus::Available => "available",
+            CopyStatus::OnLoan => "on loan",
+            CopyStatus::Lost => "lost",
+            CopyStatus::InRepair => "in repair",
+            CopyStatus::OnHold => "on hold",
+        })
+    }
+}
+
+// 一本实体书
+#[derive(Debug)]
+pub struct BookCopy {
+    pub barcode: String,    // 条码，在整个图书馆内唯一
+    pub book: Rc<Book>,     // 对应的书籍，与书籍列表共享同一个实例
+    pub status: CopyStatus, // 当前状态
+}
+
+impl Library {
+    // 为已有的书在 date_str 这天添加一本指定条码的副本；有人排队预约时直接留给排在最前的读者
+    pub fn add_copy<'a>(
+        &mut self,
+        book: impl Into<BookKey<'a>>,
+        barcode: impl Into<String>,
+        date_str: &str,
+    ) -> Result<&BookCopy, LibraryError> {
+        let book = self.find_book(book)?;
+        let date = parse_date(date_str)?;
+        let barcode = barcode.into();
+        if self.copy(&barcode).is_some() {
+            return Err(LibraryError::DuplicateBarcode { barcode });
+        }
+        self.copies.push(BookCopy {
+            barcode,
+            book,
+            status: CopyStatus::Available,
+        });
+        let index = self.copies.len() - 1;
+        self.shelve(index, date);
+        Ok(&self.copies[index])
+    }
+
+    // 按条码查找副本
+    pub fn copy(&self, barcode: &str) -> Option<&BookCopy> {
+        self.copies.iter().find(|copy| copy.barcode == barcode)
+    }
+
+    // 某种书的全部副本
+    pub fn copies_of<'a>(
+        &self,
+        book: impl Into<BookKey<'a>>,
+    ) -> Result<Vec<&BookCopy>, LibraryError> {
+        let book = self.find_book(book)?;
+        Ok(self
+            .copies
+            .iter()
+            .filter(|copy| Rc::ptr_eq(&copy.book, &book))
+            .collect())
+    }
+
+    // 某种书当前可借的副本数
+    pub fn available_copies<'a>(
+        &self,
+        book: impl Into<BookKey<'a>>,
+    ) -> Result<usize, LibraryError> {
+        Ok(self
+            .copies_of(book)?
+            .into_iter()
+            .filter(|copy| copy.status == CopyStatus::Available)
+            .count())
+    }
+
+    // 在 date_str 这天标记副本遗失、送修或修好上架。借出和预约留出的状态只能通过借还和预约改变，
+    // 借出中的副本只能标记为遗失，找回后照常归还，归还前不能改为其他状态；留出的副本要先取消预约。
+    // 修好或找回上架时有人排队预约，副本直接留给排在最前的读者
+    pub fn set_copy_status(
+        &mut self,
+        barcode: &str,
+        status: CopyStatus,
+        date_str: &str,
+    ) -> Result<&BookCopy, LibraryError> {
+        let index = self.copy_index(barcode)?;
+        let date = parse_date(date_str)?;
+        let on_loan = self.open_record_of(barcode).is_some();
+        let copy = &mut self.copies[index];
+        let from = copy.status;
+        let allowed = match (from, status) {
+            (_, CopyStatus::OnLoan | CopyStatus::OnHold) => false,
+            (CopyStatus::OnLoan, to) => to == CopyStatus::Lost,
+            (CopyStatus::Lost, to) if on_loan => to == CopyStatus::Lost,
+            (CopyStatus::OnHold, _) => false,
+            _ => true,
+        };
+        if !allowed {
+            return Err(LibraryError::InvalidStatusChange {
+                barcode: barcode.to_string(),
+                from,
+                to: status,
+            });
+        }
+        match status {
+            CopyStatus::Available if from != CopyStatus::Available => self.shelve(index, date),
+            _ => copy.status = status,
+        }
+        Ok(&self.copies[index])
+    }
+
+    // 把指定条码的副本借给读者；留给预约的副本只能借给预约的读者
+    pub fn borrow_copy(
+        &mut self,
+        member: MemberId,
+        barcode: &str,
+        borrow_date_str: &str,
+    ) -> Result<&BorrowRecord, LibraryError> {
+        self.check_borrower(member)?;
+        let index = self.copy_index(barcode)?;
+        let borrowed_date = parse_date(borrow_date_str)?;
+        let category = &self.copies[index].book.category;
+        let due_date = self.loan_policy.due_date(category, borrowed_date)?;
+        match self.copies[index].status {
+            CopyStatus::Available => Ok(self.lend(index, member, borrowed_date, due_date)),
+            CopyStatus::OnHold
+                if self
+                    .hold_on_copy(barcode)
+                    .is_some_and(|hold| self.holds[hold].member == member) =>
+            {
+                let hold = self.hold_on_copy(barcode).unwrap();
+                self.collect_hold(hold);
+                Ok(self.lend(index, member, borrowed_date, due_date))
+            }
+            CopyStatus::OnLoan => {
+                let record = self.open_record_of(barcode).unwrap();
+                Err(LibraryError::AlreadyBorrowed {
+                    title: record.book.title.clone(),
+                    barcode: barcode.to_string(),
+                    borrowed_date: record.borrowed_date,
+                })
+            }
+            status => Err(LibraryError::CopyUnavailable {
+                barcode: barcode.to_string(),
+                status,
+            }),
+        }
+    }
+
+    // 归还指定条码的副本；遗失后找回的副本也通过它归还
+    pub fn return_copy(
+        &mut self,
+        barcode: &str,
+        return_date_str: &str,
+    ) -> Result<&BorrowRecord, LibraryError> {
+        let index = self.copy_index(barcode)?;
+        let return_date = parse_date(return_date_str)?;
+        let record = self
+            .borrow_records
+            .iter()
+            .position(|record| record.barcode == barcode && record.return_date.is_none())
+            .ok_or_else(|| LibraryError::NotBorrowed {
+                title: self.copies[index].book.title.clone(),
+            })?;
+        self.close(record, return_date)
+    }
+
+    pub(super) fn copy_index(&self, barcode: &str) -> Result<usize, LibraryError> {
+        self.copies
+            .iter()
+            .position(|copy| copy.barcode == barcode)
+            .ok_or_else(|| LibraryError::UnknownBarcode {
+                barcode: barcode.to_string(),
+            })
+    }
+
+    fn open_record_of(&self, barcode: &str) -> Option<&BorrowRecord> {
+        self.borrow_records
+            .iter()
+            .find(|record| record.barcode == barcode && record.return_date.is_none())
+    }
+
+    // 自动生成的条码：C000001、C000002……跳过已被占用的
+    pub(super) fn next_barcode(&self) -> String {
+        (self.copies.len() + 1..)
+            .map(|n| format!("C{:06}", n))
+            .find(|barcode| self.copy(barcode).is_none())
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_copies() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    let reader = library.register_member("Alice", "alice@example.com").id;
+    assert_eq!(
+        library.copies_of("Rust Programming").unwrap()[0].barcode,
+        "C000001"
+    );
+    library
+        .add_copy("Rust Programming", "RP-2", "2023-03-31")
+        .unwrap();
+    library
+        .add_copy("Rust Programming", "RP-3", "2023-03-31")
+        .unwrap();
+    assert_eq!(
+        library
+            .add_copy("Rust Programming", "RP-2", "2023-03-31")
+            .unwrap_err(),
+        LibraryError::DuplicateBarcode {
+            barcode: "RP-2".into()
+        }
+    );
+    assert_eq!(library.available_copies("Rust Programming"), Ok(3));
+
+    // 依次分配可借的副本，全部借出后借书失败
+    let barcodes: Vec<_> = (1..=3)
+        .map(|day| {
+            let record = library
+                .borrow_book(reader, "Rust Programming", &format!("2023-04-0{}", day))
+                .unwrap();
+            record.barcode.clone()
+        })
+        .collect();
+    assert_eq!(barcodes, ["C000001", "RP-2", "RP-3"]);
+    assert_eq!(library.available_copies("Rust Programming"), Ok(0));
+    assert_eq!(
+        library
+            .borrow_book(reader, "Rust Programming", "2023-04-04")
+            .unwrap_err(),
+        LibraryError::NoCopyAvailable {
+            title: "Rust Programming".into()
+        }
+    );
+
+    // 按条码归还指定的副本，按书名归还最早借出的副本
+    let record = library.return_copy("RP-2", "2023-04-10").unwrap();
+    assert_eq!(record.borrowed_date, date("2023-04-02"));
+    let record = library
+        .return_book("Rust Programming", "2023-04-11")
+        .unwrap();
+    assert_eq!(record.barcode, "C000001");
+    assert_eq!(library.available_copies("Rust Programming"), Ok(2));
+    assert_eq!(
+        library
+            .borrow_copy(reader, "RP-3", "2023-04-12")
+            .unwrap_err(),
+        LibraryError::AlreadyBorrowed {
+            title: "Rust Programming".into(),
+            barcode: "RP-3".into(),
+            borrowed_date: date("2023-04-03"),
+        }
+    );
+    assert_eq!(library.copy("RP-3").unwrap().status, CopyStatus::OnLoan);
+}
+
+#[test]
+fn test_copy_status() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    let reader = library.register_member("Alice", "alice@example.com").id;
+    library
+        .add_copy("Rust Programming", "RP-2", "2023-03-31")
+        .unwrap();
+
+    // 送修的副本不会被分配，也不能指定借出
+    library
+        .set_copy_status("RP-2", CopyStatus::InRepair, "2023-04-01")
+        .unwrap();
+    assert_eq!(library.available_copies("Rust Programming"), Ok(1));
+    assert_eq!(
+        library
+            .borrow_copy(reader, "RP-2", "2023-04-01")
+            .unwrap_err(),
+        LibraryError::CopyUnavailable {
+            barcode: "RP-2".into(),
+            status: CopyStatus::InRepair
+        }
+    );
+    assert_eq!(
+        library
+            .set_copy_status("RP-2", CopyStatus::OnLoan, "2023-04-01")
+            .unwrap_err(),
+        LibraryError::InvalidStatusChange {
+            barcode: "RP-2".into(),
+            from: CopyStatus::InRepair,
+            to: CopyStatus::OnLoan
+        }
+    );
+
+    // 借出中的副本只能标记遗失，找回后归还即恢复可借
+    library
+        .borrow_copy(reader, "C000001", "2023-04-01")
+        .unwrap();
+    assert!(library
+        .set_copy_status("C000001", CopyStatus::Available, "2023-04-10")
+        .is_err());
+    library
+        .set_copy_status("C000001", CopyStatus::Lost, "2023-04-10")
+        .unwrap();
+    // 遗失时仍未归还，不能直接上架再借出，否则一本副本会有两条未归还的记录
+    for status in [CopyStatus::Available, CopyStatus::InRepair] {
+        assert_eq!(
+            library
+                .set_copy_status("C000001", status, "2023-04-15")
+                .unwrap_err(),
+            LibraryError::InvalidStatusChange {
+                barcode: "C000001".into(),
+                from: CopyStatus::Lost,
+                to: status
+            }
+        );
+    }
+    assert!(library
+        .borrow_copy(reader, "C000001", "2023-04-20")
+        .is_err());
+    library.return_copy("C000001", "2023-05-01").unwrap();
+    assert_eq!(
+        library.copy("C000001").unwrap().status,
+        CopyStatus::Available
+    );
+
+    assert_eq!(
+        library.return_copy("X", "2023-05-01").unwrap_err(),
+        LibraryError::UnknownBarcode {
+            barcode: "X".into()
+        }
+    );
+    assert!(library.available_copies("Go Programming").is_err());
+}
@@ -0,0 +1,435 @@
+// 预约：书全部借出时读者可以排队，有副本归还时按预约的先后留给排在最前的读者，
+// 读者须在取书期限内来借，过期或取消后副本留给下一位
+#[cfg(test)]
+use super::Author;
+use super::{parse_date, Book, BookKey, CopyStatus, Library, LibraryError, MemberId};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::rc::Rc;
+
+// 默认的取书期限
+pub const DEFAULT_PICKUP_DAYS: u32 = 7;
+
+// 预约编号，按顺序分配，从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct HoldId(pub(super) u32);
+
+impl HoldId {
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for HoldId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "H{:04}", self.0)
+    }
+}
+
+// 预约的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HoldStatus {
+    Waiting,   // 排队中
+    Ready,     // 已留出副本，等待读者来取
+    Fulfilled, // 读者已借走
+    Cancelled, // 已取消
+    Expired,   // 超过取书期限
+}
+
+impl fmt::Display for HoldStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            HoldStatus::Waiting => "waiting",
+            HoldStatus::Ready => "ready",
+            HoldStatus::Fulfilled => "fulfilled",
+            HoldStatus::Cancelled => "cancelled",
+            HoldStatus::Expired => "expired",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Hold {
+    pub id: HoldId,
+    pub book: Rc<Book>,             // 预约的书籍，与书籍列表共享同一个实例
+    pub member: MemberId,           // 预约的读者
+    pub placed_date: NaiveDate,     // 预约日期
+    pub status: HoldStatus,         // 当前状态
+    pub barcode: Option<String>,    // 留给读者的副本，Ready 之后才有
+    pub expires: Option<NaiveDate>, // 取书期限的最后一天，Ready 之后才有
+}
+
+impl Hold {
+    // 排队中或等待取书的预约
+    pub fn is_active(&self) -> bool {
+        matches!(self.status, HoldStatus::Waiting | HoldStatus::Ready)
+    }
+}
+
+impl Library {
+    // 预约一种全部借出的书。还有可借副本时直接借即可，不接受预约；
+    // 同一读者对同一种书只能有一个有效预约
+    pub fn place_hold<'a>(
+        &mut self,
+        member: MemberId,
+        book: impl Into<BookKey<'a>>,
+        date_str: &str,
+    ) -> Result<&Hold, LibraryError> {
+        self.check_borrower(member)?;
+        let book = self.find_book(book)?;
+        let placed_date = parse_date(date_str)?;
+        if self.available_copies(&book)? > 0 {
+            return Err(LibraryError::HoldNotNeeded {
+                title: book.title.clone(),
+            });
+        }
+        if self
+            .holds
+            .iter()
+            .any(|hold| hold.is_active() && hold.member == member && Rc::ptr_eq(&hold.book, &book))
+        {
+            return Err(LibraryError::DuplicateHold {
+                id: member,
+                title: book.title.clone(),
+            });
+        }
+        let id = HoldId(self.last_ids.hold + 1);
+        self.last_ids.hold = id.0;
+        self.holds.push(Hold {
+            id,
+            book,
+            member,
+            placed_date,
+            status: HoldStatus::Waiting,
+            barcode: None,
+            expires: None,
+        });
+        Ok(self.holds.last().unwrap())
+    }
+
+    pub fn hold(&self, id: HoldId) -> Option<&Hold> {
+        self.holds.iter().find(|hold| hold.id == id)
+    }
+
+    // 某种书的有效预约，按排队先后排列
+    pub fn holds_for<'a>(&self, book: impl Into<BookKey<'a>>) -> Result<Vec<&Hold>, LibraryError> {
+        let book = self.find_book(book)?;
+        Ok(self
+            .holds
+            .iter()
+            .filter(|hold| hold.is_active() && Rc::ptr_eq(&hold.book, &book))
+            .collect())
+    }
+
+    // 取消预约；已经留出的副本转给下一位排队的读者
+    pub fn cancel_hold(&mut self, id: HoldId, date_str: &str) -> Result<&Hold, LibraryError> {
+        let index = self.hold_index(id)?;
+        let date = parse_date(date_str)?;
+        let hold = &self.holds[index];
+        if !hold.is_active() {
+            return Err(LibraryError::HoldClosed {
+                id,
+                status: hold.status,
+            });
+        }
+        self.release(index, HoldStatus::Cancelled, date);
+        Ok(&self.holds[index])
+    }
+
+    // 把到 today 已超过取书期限的预约标记为过期，副本转给下一位，返回过期的预约编号
+    pub fn expire_holds(&mut self, today_str: &str) -> Result<Vec<HoldId>, LibraryError> {
+        let today = parse_date(today_str)?;
+        let mut expired = Vec::new();
+        for index in 0..self.holds.len() {
+            let hold = &self.holds[index];
+            if hold.status == HoldStatus::Ready && hold.expires.is_some_and(|last| last < today) {
+                expired.push(hold.id);
+                self.release(index, HoldStatus::Expired, today);
+            }
+        }
+        Ok(expired)
+    }
+
+    pub fn pickup_days(&self) -> u32 {
+        self.pickup_days
+    }
+
+    // 只影响之后留出的副本，已经在等待取书的预约期限不变
+    pub fn set_pickup_days(&mut self, days: u32) {
+        self.pickup_days = days;
+    }
+
+    fn hold_index(&self, id: HoldId) -> Result<usize, LibraryError> {
+        self.holds
+            .iter()
+            .position(|hold| hold.id == id)
+            .ok_or(LibraryError::UnknownHold { id })
+    }
+
+    // 结束第 index 个预约，它留出的副本重新上架
+    fn release(&mut self, index: usize, status: HoldStatus, date: NaiveDate) {
+        let hold = &mut self.holds[index];
+        hold.status = status;
+        if let Some(barcode) = hold.barcode.clone() {
+            let copy = self.copy_index(&barcode).unwrap();
+            self.shelve(copy, date);
+        }
+    }
+
+    // 读者 member 对这种书已留出副本的预约
+    pub(super) fn ready_hold(&self, member: MemberId, book: &Rc<Book>) -> Option<usize> {
+        self.holds.iter().position(|hold| {
+            hold.status == HoldStatus::Ready
+                && hold.member == member
+                && Rc::ptr_eq(&hold.book, book)
+        })
+    }
+
+    // 留出副本 barcode 的预约
+    pub(super) fn hold_on_copy(&self, barcode: &str) -> Option<usize> {
+        self.holds.iter().position(|hold| {
+            hold.status == HoldStatus::Ready && hold.barcode.as_deref() == Some(barcode)
+        })
+    }
+
+    // 读者来取第 index 个预约留出的副本：预约完成，返回副本的下标
+    pub(super) fn collect_hold(&mut self, index: usize) -> usize {
+        let hold = &mut self.holds[index];
+        hold.status = HoldStatus::Fulfilled;
+        let barcode = hold.barcode.clone().unwrap();
+        self.copy_index(&barcode).unwrap()
+    }
+
+    // 副本在 date 回到馆里：有人排队就留给排在最前的读者，否则上架可借
+    pub(super) fn shelve(&mut self, copy: usize, date: NaiveDate) {
+        let book = &self.copies[copy].book;
+        let next = self
+            .holds
+            .iter_mut()
+            .find(|hold| hold.status == HoldStatus::Waiting && Rc::ptr_eq(&hold.book, book));
+        match next {
+            Some(hold) => {
+                hold.status = HoldStatus::Ready;
+                hold.barcode = Some(self.copies[copy].barcode.clone());
+                // 取书期限只是截止日期，超出日期范围时按最大日期算，不影响还书
+                let expires = date.checked_add_days(Days::new(self.pickup_days.into()));
+                hold.expires = Some(expires.unwrap_or(NaiveDate::MAX));
+                self.copies[copy].status = CopyStatus::OnHold;
+            }
+            None => self.copies[copy].status = CopyStatus::Available,
+        }
+    }
+}
+
+#[cfg(test)]
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_hold_queue() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    let carol = library.register_member("Carol", "").id;
+
+    assert_eq!(
+        library
+            .place_hold(bob, "Rust Programming", "2023-04-01")
+            .unwrap_err(),
+        LibraryError::HoldNotNeeded {
+            title: "Rust Programming".into()
+        }
+    );
+    library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap();
+    let bob_hold = library
+        .place_hold(bob, "Rust Programming", "2023-04-02")
+        .unwrap()
+        .id;
+    let carol_hold = library
+        .place_hold(carol, "Rust Programming", "2023-04-03")
+        .unwrap()
+        .id;
+    assert_eq!(bob_hold.to_string(), "H0001");
+    assert_eq!(
+        library
+            .place_hold(bob, "Rust Programming", "2023-04-04")
+            .unwrap_err(),
+        LibraryError::DuplicateHold {
+            id: bob,
+            title: "Rust Programming".into()
+        }
+    );
+
+    // 还书后副本留给排在最前的 Bob，其他人借不到
+    library
+        .return_book("Rust Programming", "2023-04-10")
+        .unwrap();
+    let hold = library.hold(bob_hold).unwrap();
+    assert_eq!(hold.status, HoldStatus::Ready);
+    assert_eq!(hold.barcode.as_deref(), Some("C000001"));
+    assert_eq!(hold.expires, Some(date("2023-04-17")));
+    assert_eq!(library.copy("C000001").unwrap().status, CopyStatus::OnHold);
+    assert_eq!(
+        library
+            .borrow_book(alice, "Rust Programming", "2023-04-11")
+            .unwrap_err(),
+        LibraryError::NoCopyAvailable {
+            title: "Rust Programming".into()
+        }
+    );
+    assert_eq!(
+        library
+            .borrow_copy(carol, "C000001", "2023-04-11")
+            .unwrap_err(),
+        LibraryError::CopyUnavailable {
+            barcode: "C000001".into(),
+            status: CopyStatus::OnHold
+        }
+    );
+
+    // Bob 取书后轮到 Carol
+    let record = library
+        .borrow_book(bob, "Rust Programming", "2023-04-12")
+        .unwrap();
+    assert_eq!(record.borrower, bob);
+    assert_eq!(
+        library.hold(bob_hold).unwrap().status,
+        HoldStatus::Fulfilled
+    );
+    let queue: Vec<_> = library
+        .holds_for("Rust Programming")
+        .unwrap()
+        .iter()
+        .map(|hold| hold.id)
+        .collect();
+    assert_eq!(queue, [carol_hold]);
+}
+
+#[test]
+fn test_hold_expiry_and_cancel() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    library.set_pickup_days(3);
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    let carol = library.register_member("Carol", "").id;
+
+    library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap();
+    let bob_hold = library
+        .place_hold(bob, "Rust Programming", "2023-04-02")
+        .unwrap()
+        .id;
+    let carol_hold = library
+        .place_hold(carol, "Rust Programming", "2023-04-03")
+        .unwrap()
+        .id;
+    library.return_copy("C000001", "2023-04-10").unwrap();
+
+    // 期限的最后一天仍可取书，过了期限副本转给 Carol
+    assert!(library.expire_holds("2023-04-13").unwrap().is_empty());
+    assert_eq!(library.expire_holds("2023-04-14").unwrap(), [bob_hold]);
+    assert_eq!(library.hold(bob_hold).unwrap().status, HoldStatus::Expired);
+    let hold = library.hold(carol_hold).unwrap();
+    assert_eq!(hold.status, HoldStatus::Ready);
+    assert_eq!(hold.expires, Some(date("2023-04-17")));
+
+    // Carol 取消后没人排队，副本重新上架
+    library.cancel_hold(carol_hold, "2023-04-15").unwrap();
+    assert_eq!(
+        library.cancel_hold(carol_hold, "2023-04-15").unwrap_err(),
+        LibraryError::HoldClosed {
+            id: carol_hold,
+            status: HoldStatus::Cancelled
+        }
+    );
+    assert_eq!(library.available_copies("Rust Programming"), Ok(1));
+    assert!(library.holds_for("Rust Programming").unwrap().is_empty());
+    library
+        .borrow_book(bob, "Rust Programming", "2023-04-16")
+        .unwrap();
+
+    let stranger = HoldId(99);
+    assert_eq!(
+        library.cancel_hold(stranger, "2023-04-16").unwrap_err(),
+        LibraryError::UnknownHold { id: stranger }
+    );
+}
+
+#[test]
+fn test_hold_served_when_copy_shelved() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    library
+        .add_copy("Rust Programming", "RP-2", "2023-03-31")
+        .unwrap();
+    library
+        .set_copy_status("RP-2", CopyStatus::InRepair, "2023-03-31")
+        .unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    let carol = library.register_member("Carol", "").id;
+
+    library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap();
+    let bob_hold = library
+        .place_hold(bob, "Rust Programming", "2023-04-02")
+        .unwrap()
+        .id;
+
+    // 修好上架时有人排队，副本直接留给 Bob，不会被别人先借走
+    let copy = library
+        .set_copy_status("RP-2", CopyStatus::Available, "2023-04-05")
+        .unwrap();
+    assert_eq!(copy.status, CopyStatus::OnHold);
+    let hold = library.hold(bob_hold).unwrap();
+    assert_eq!(hold.status, HoldStatus::Ready);
+    assert_eq!(hold.barcode.as_deref(), Some("RP-2"));
+    assert_eq!(hold.expires, Some(date("2023-04-12")));
+    assert_eq!(library.available_copies("Rust Programming"), Ok(0));
+    assert_eq!(
+        library
+            .borrow_copy(carol, "RP-2", "2023-04-06")
+            .unwrap_err(),
+        LibraryError::CopyUnavailable {
+            barcode: "RP-2".into(),
+            status: CopyStatus::OnHold
+        }
+    );
+    library
+        .borrow_book(bob, "Rust Programming", "2023-04-06")
+        .unwrap();
+
+    // 新入藏的副本同样先满足排队的预约
+    let carol_hold = library
+        .place_hold(carol, "Rust Programming", "2023-04-07")
+        .unwrap()
+        .id;
+    let copy = library
+        .add_copy("Rust Programming", "RP-3", "2023-04-08")
+        .unwrap();
+    assert_eq!(copy.status, CopyStatus::OnHold);
+    let hold = library.hold(carol_hold).unwrap();
+    assert_eq!(hold.barcode.as_deref(), Some("RP-3"));
+    assert_eq!(hold.expires, Some(date("2023-04-15")));
+}
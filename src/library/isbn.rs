@@ -0,0 +1,176 @@
+// ISBN：解析 ISBN-10 和 ISBN-13，校验校验位，两种形式互相转换。
+// 内部统一保存为 ISBN-13，ISBN-10 等价于前缀 978 的 ISBN-13
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::{error, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Isbn([u8; 13]);
+
+// 解析 ISBN 可能出现的错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IsbnError {
+    // 去掉连字符和空格后既不是 10 位也不是 13 位
+    InvalidLength { len: usize },
+    // 出现了数字以外的字符；X 只能作为 ISBN-10 的校验位
+    InvalidCharacter { ch: char },
+    // 校验位不对
+    BadChecksum,
+}
+
+impl fmt::Display for IsbnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IsbnError::InvalidLength { len } => {
+                write!(f, "ISBN must have 10 or 13 digits, found {}", len)
+            }
+            IsbnError::InvalidCharacter { ch } => write!(f, "invalid character '{}' in ISBN", ch),
+            IsbnError::BadChecksum => f.write_str("ISBN checksum does not match"),
+        }
+    }
+}
+
+impl error::Error for IsbnError {}
+
+impl Isbn {
+    // 解析 ISBN-10 或 ISBN-13，允许用连字符或空格分组
+    pub fn parse(input: &str) -> Result<Isbn, IsbnError> {
+        let chars: Vec<char> = input.chars().filter(|&ch| ch != '-' && ch != ' ').collect();
+        match chars.len() {
+            10 => Isbn::parse10(&chars),
+            13 => {
+                let mut digits = [0; 13];
+                for (digit, &ch) in digits.iter_mut().zip(&chars) {
+                    *digit = to_digit(ch)?;
+                }
+                if check13(&digits[..12]) != digits[12] {
+                    return Err(IsbnError::BadChecksum);
+                }
+                Ok(Isbn(digits))
+            }
+            len => Err(IsbnError::InvalidLength { len }),
+        }
+    }
+
+    fn parse10(chars: &[char]) -> Result<Isbn, IsbnError> {
+        let mut digits = [9, 7, 8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        for (digit, &ch) in digits[3..12].iter_mut().zip(&chars[..9]) {
+            *digit = to_digit(ch)?;
+        }
+        let check = match chars[9] {
+            'X' | 'x' => 10,
+            ch => to_digit(ch)?,
+        };
+        if check10(&digits[3..12]) != check {
+            return Err(IsbnError::BadChecksum);
+        }
+        digits[12] = check13(&digits[..12]);
+        Ok(Isbn(digits))
+    }
+
+    // 13 位的形式，不带连字符
+    pub fn to_isbn13(&self) -> String {
+        self.0.iter().map(|&d| char::from(b'0' + d)).collect()
+    }
+
+    // 10 位的形式，不带连字符；只有前缀 978 的 ISBN 有对应的 ISBN-10
+    pub fn to_isbn10(&self) -> Option<String> {
+        if self.0[..3] != [9, 7, 8] {
+            return None;
+        }
+        let mut isbn: String = self.0[3..12]
+            .iter()
+            .map(|&d| char::from(b'0' + d))
+            .collect();
+        isbn.push(match check10(&self.0[3..12]) {
+            10 => 'X',
+            d => char::from(b'0' + d),
+        });
+        Some(isbn)
+    }
+}
+
+fn to_digit(ch: char) -> Result<u8, IsbnError> {
+    ch.to_digit(10)
+        .map(|d| d as u8)
+        .ok_or(IsbnError::InvalidCharacter { ch })
+}
+
+// ISBN-10 的校验位，10 表示 X
+fn check10(digits: &[u8]) -> u8 {
+    let sum: u32 = (0..9)
+        .map(|i| (10 - i) * u32::from(digits[i as usize]))
+        .sum();
+    ((11 - sum % 11) % 11) as u8
+}
+
+// ISBN-13 的校验位，前 12 位交替乘 1 和 3
+fn check13(digits: &[u8]) -> u8 {
+    let sum: u32 = digits
+        .iter()
+        .enumerate()
+        .map(|(i, &d)| u32::from(d) * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+impl fmt::Display for Isbn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_isbn13())
+    }
+}
+
+impl FromStr for Isbn {
+    type Err = IsbnError;
+
+    fn from_str(s: &str) -> Result<Isbn, IsbnError> {
+        Isbn::parse(s)
+    }
+}
+
+impl TryFrom<String> for Isbn {
+    type Error = IsbnError;
+
+    fn try_from(s: String) -> Result<Isbn, IsbnError> {
+        Isbn::parse(&s)
+    }
+}
+
+impl From<Isbn> for String {
+    fn from(isbn: Isbn) -> String {
+        isbn.to_isbn13()
+    }
+}
+
+#[test]
+fn test_isbn() {
+    let isbn: Isbn = "978-1-7185-0044-0".parse().unwrap();
+    assert_eq!(isbn.to_string(), "9781718500440");
+    assert_eq!(isbn.to_isbn10().as_deref(), Some("1718500440"));
+    // 两种形式解析出同一个 ISBN
+    assert_eq!(Isbn::parse("1-7185-0044-0"), Ok(isbn));
+    let isbn = Isbn::parse("0 8044 2957 x").unwrap();
+    assert_eq!(isbn.to_isbn13(), "9780804429573");
+    assert_eq!(isbn.to_isbn10().as_deref(), Some("080442957X"));
+    assert_eq!(Isbn::parse("979-10-90636-07-1").unwrap().to_isbn10(), None);
+
+    assert_eq!(
+        Isbn::parse("978-1-7185-0044-1"),
+        Err(IsbnError::BadChecksum)
+    );
+    assert_eq!(Isbn::parse("1718500441"), Err(IsbnError::BadChecksum));
+    assert_eq!(
+        Isbn::parse("978-1-7185"),
+        Err(IsbnError::InvalidLength { len: 8 })
+    );
+    assert_eq!(
+        Isbn::parse("X718500440"),
+        Err(IsbnError::InvalidCharacter { ch: 'X' })
+    );
+    assert_eq!(
+        serde_json::to_string(&Isbn::parse("1718500440").unwrap()).unwrap(),
+        r#""9781718500440""#
+    );
+    assert!(serde_json::from_str::<Isbn>(r#""9781718500441""#).is_err());
+}
@@ -0,0 +1,193 @@
+// 书籍的标识：图书馆内部编号、ISBN 或书名。查找都走索引，
+// 按书名查找要求书名唯一，同名的书必须用编号或 ISBN 区分
+#[cfg(test)]
+use super::Author;
+use super::{Book, Isbn, Library, LibraryError, SUGGESTION_LIMIT};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::rc::Rc;
+
+// 书籍编号，入藏时按顺序分配，从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BookId(pub(super) u32);
+
+impl BookId {
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for BookId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "B{:04}", self.0)
+    }
+}
+
+// 指定一种书的方式，图书馆的接口都接受能转换为它的值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookKey<'a> {
+    Id(BookId),
+    Isbn(Isbn),
+    Title(&'a str),
+}
+
+impl fmt::Display for BookKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookKey::Id(id) => id.fmt(f),
+            BookKey::Isbn(isbn) => write!(f, "ISBN {}", isbn),
+            BookKey::Title(title) => f.write_str(title),
+        }
+    }
+}
+
+impl From<BookId> for BookKey<'_> {
+    fn from(id: BookId) -> Self {
+        BookKey::Id(id)
+    }
+}
+
+impl From<Isbn> for BookKey<'_> {
+    fn from(isbn: Isbn) -> Self {
+        BookKey::Isbn(isbn)
+    }
+}
+
+impl<'a> From<&'a str> for BookKey<'a> {
+    fn from(title: &'a str) -> Self {
+        BookKey::Title(title)
+    }
+}
+
+impl<'a> From<&'a Book> for BookKey<'a> {
+    fn from(book: &'a Book) -> Self {
+        BookKey::Id(book.id)
+    }
+}
+
+impl<'a> From<&'a Rc<Book>> for BookKey<'a> {
+    fn from(book: &'a Rc<Book>) -> Self {
+        BookKey::Id(book.id)
+    }
+}
+
+impl Library {
+    // 按编号、ISBN 或书名查找书籍
+    pub fn book<'a>(&self, key: impl Into<BookKey<'a>>) -> Option<&Rc<Book>> {
+        self.find_book(key)
+            .ok()
+            .and_then(|book| self.by_id.get(&book.id))
+    }
+
+    pub(super) fn find_book<'a>(
+        &self,
+        key: impl Into<BookKey<'a>>,
+    ) -> Result<Rc<Book>, LibraryError> {
+        let key = key.into();
+        let found = match key {
+            BookKey::Id(id) => self.by_id.get(&id).cloned(),
+            BookKey::Isbn(isbn) => self.by_isbn.get(&isbn).cloned(),
+            BookKey::Title(title) => {
+                let mut books = self
+                    .catalog
+                    .same_title(title)
+                    .map(|id| &self.by_id[&id])
+                    .filter(|book| book.title == title);
+                let first = books.next().cloned();
+                let more = books.count();
+                if more > 0 {
+                    return Err(LibraryError::AmbiguousTitle {
+                        title: title.to_string(),
+                        count: more + 1,
+                    });
+                }
+                first
+            }
+        };
+        found.ok_or_else(|| LibraryError::NotFound {
+            key: key.to_string(),
+            suggestions: match key {
+                BookKey::Title(title) => self.suggest_titles(title, SUGGESTION_LIMIT),
+                _ => Vec::new(),
+            },
+        })
+    }
+
+    // 把书籍加入书籍列表和各个索引，ISBN 不能重复
+    pub(super) fn push_book(&mut self, book: Book) -> Result<Rc<Book>, LibraryError> {
+        if let Some(isbn) = book.isbn {
+            if self.by_isbn.contains_key(&isbn) {
+                return Err(LibraryError::DuplicateIsbn { isbn });
+            }
+        }
+        let book = Rc::new(book);
+        if let Some(isbn) = book.isbn {
+            self.by_isbn.insert(isbn, Rc::clone(&book));
+        }
+        self.by_id.insert(book.id, Rc::clone(&book));
+        self.catalog.insert(&book);
+        self.books.push(Rc::clone(&book));
+        self.last_ids.book = self.last_ids.book.max(book.id.0);
+        Ok(book)
+    }
+
+    pub(super) fn next_book_id(&self) -> BookId {
+        BookId(self.last_ids.book + 1)
+    }
+}
+
+#[test]
+fn test_book_keys() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let isbn = Isbn::parse("978-1-7185-0044-0").unwrap();
+    let mut library = Library::new();
+    let first = library
+        .add_book("The Rust Book".into(), Rc::clone(&author))
+        .unwrap();
+    let second = library
+        .add_book_with_isbn("The Rust Book".into(), Rc::clone(&author), isbn)
+        .unwrap();
+    assert_eq!(
+        (first.id.to_string(), second.id.to_string()),
+        ("B0001".into(), "B0002".into())
+    );
+    assert_eq!(
+        library
+            .add_book_with_isbn("Copy".into(), author, Isbn::parse("1718500440").unwrap())
+            .unwrap_err(),
+        LibraryError::DuplicateIsbn { isbn }
+    );
+
+    // 同名的书只能用编号或 ISBN 指定
+    assert!(Rc::ptr_eq(library.book(isbn).unwrap(), &second));
+    assert!(Rc::ptr_eq(library.book(first.id).unwrap(), &first));
+    assert_eq!(
+        library.available_copies("The Rust Book").unwrap_err(),
+        LibraryError::AmbiguousTitle {
+            title: "The Rust Book".into(),
+            count: 2
+        }
+    );
+    let reader = library.register_member("Alice", "alice@example.com").id;
+    let record = library.borrow_book(reader, isbn, "2023-04-01").unwrap();
+    assert!(Rc::ptr_eq(&record.book, &second));
+    assert_eq!(library.available_copies(&first), Ok(1));
+    assert_eq!(library.available_copies(second.id), Ok(0));
+    library.return_book(&second, "2023-04-10").unwrap();
+
+    assert_eq!(
+        library.copies_of(BookId(9)).unwrap_err(),
+        LibraryError::NotFound {
+            key: "B0009".into(),
+            suggestions: Vec::new(),
+        }
+    );
+    let unknown = Isbn::parse("0131103628").unwrap();
+    assert_eq!(
+        library.copies_of(unknown).unwrap_err().to_string(),
+        "book 'ISBN 9780131103627' not found"
+    );
+}
@@ -0,0 +1,241 @@
+// 续借：在借期内把应还日期再延长一个借期。续借次数有上限，有人排队预约时不能续借，
+// 已逾期的能否续借由续借规则决定。每次续借都记在借阅记录里
+use super::policy::add_days;
+use super::{parse_date, BorrowRecord, HoldStatus, Library, LibraryError};
+#[cfg(test)]
+use super::{Author, LoanPolicy};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::rc::Rc;
+
+// 借阅编号，借书时按顺序分配，从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LoanId(pub(super) u32);
+
+impl LoanId {
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for LoanId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "L{:04}", self.0)
+    }
+}
+
+// 一次续借
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Renewal {
+    pub date: NaiveDate,         // 续借日期
+    pub previous_due: NaiveDate, // 续借前的应还日期
+    pub due_date: NaiveDate,     // 续借后的应还日期
+}
+
+// 续借规则：每次借阅最多续借 max_renewals 次，allow_overdue 为 false 时逾期的不能续借
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenewalPolicy {
+    pub max_renewals: u32,
+    pub allow_overdue: bool,
+}
+
+impl Default for RenewalPolicy {
+    // 默认最多续借 2 次，逾期不能续借
+    fn default() -> Self {
+        RenewalPolicy {
+            max_renewals: 2,
+            allow_overdue: false,
+        }
+    }
+}
+
+impl Library {
+    pub fn loan(&self, id: LoanId) -> Option<&BorrowRecord> {
+        self.borrow_records.iter().find(|record| record.id == id)
+    }
+
+    // 在 today 续借，应还日期从原应还日期（逾期时从 today）起再延长一个借期
+    pub fn renew(&mut self, id: LoanId, today_str: &str) -> Result<&BorrowRecord, LibraryError> {
+        let index = self
+            .borrow_records
+            .iter()
+            .position(|record| record.id == id)
+            .ok_or(LibraryError::UnknownLoan { id })?;
+        let today = parse_date(today_str)?;
+        let record = &self.borrow_records[index];
+        if record.return_date.is_some() {
+            return Err(LibraryError::LoanClosed { id });
+        }
+        self.check_borrower(record.borrower)?;
+        let max = self.renewal_policy.max_renewals;
+        if record.renewals.len() >= max as usize {
+            return Err(LibraryError::RenewalLimit { id, max });
+        }
+        // 别人还在排队等这种书，应当按时归还
+        if self.holds.iter().any(|hold| {
+            hold.status == HoldStatus::Waiting
+                && hold.member != record.borrower
+                && Rc::ptr_eq(&hold.book, &record.book)
+        }) {
+            return Err(LibraryError::HoldPending {
+                title: record.book.title.clone(),
+            });
+        }
+        if today > record.due_date && !self.renewal_policy.allow_overdue {
+            return Err(LibraryError::RenewOverdue {
+                id,
+                due_date: record.due_date,
+            });
+        }
+
+        let days = self.loan_policy.loan_days(&record.book.category);
+        let previous_due = record.due_date;
+        let due_date = add_days(previous_due.max(today), days)?;
+        let record = &mut self.borrow_records[index];
+        record.due_date = due_date;
+        record.renewals.push(Renewal {
+            date: today,
+            previous_due,
+            due_date,
+        });
+        Ok(record)
+    }
+
+    pub fn renewal_policy(&self) -> &RenewalPolicy {
+        &self.renewal_policy
+    }
+
+    // 只影响之后的续借，已有的续借不变
+    pub fn set_renewal_policy(&mut self, policy: RenewalPolicy) {
+        self.renewal_policy = policy;
+    }
+
+    // 下一个借阅编号
+    pub(super) fn next_loan_id(&self) -> LoanId {
+        LoanId(self.last_ids.loan + 1)
+    }
+}
+
+#[cfg(test)]
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_renew() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let loan = library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap()
+        .id;
+    assert_eq!(loan.to_string(), "L0001");
+
+    // 两次续借各延长 21 天，第三次超过上限
+    let record = library.renew(loan, "2023-04-20").unwrap();
+    assert_eq!(record.due_date, date("2023-05-13"));
+    library.renew(loan, "2023-05-10").unwrap();
+    assert_eq!(
+        library.renew(loan, "2023-05-20").unwrap_err(),
+        LibraryError::RenewalLimit { id: loan, max: 2 }
+    );
+    let record = library.loan(loan).unwrap();
+    assert_eq!(record.due_date, date("2023-06-03"));
+    assert_eq!(
+        record.renewals,
+        [
+            Renewal {
+                date: date("2023-04-20"),
+                previous_due: date("2023-04-22"),
+                due_date: date("2023-05-13"),
+            },
+            Renewal {
+                date: date("2023-05-10"),
+                previous_due: date("2023-05-13"),
+                due_date: date("2023-06-03"),
+            },
+        ]
+    );
+
+    library
+        .return_book("Rust Programming", "2023-05-30")
+        .unwrap();
+    assert_eq!(
+        library.renew(loan, "2023-05-31").unwrap_err(),
+        LibraryError::LoanClosed { id: loan }
+    );
+    let stranger = LoanId(99);
+    assert_eq!(
+        library.renew(stranger, "2023-05-31").unwrap_err(),
+        LibraryError::UnknownLoan { id: stranger }
+    );
+}
+
+#[test]
+fn test_renew_refused() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    let loan = library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap()
+        .id;
+
+    // 逾期默认不能续借，规则允许时从续借当天起算
+    assert_eq!(
+        library.renew(loan, "2023-04-25").unwrap_err(),
+        LibraryError::RenewOverdue {
+            id: loan,
+            due_date: date("2023-04-22")
+        }
+    );
+    library.set_renewal_policy(RenewalPolicy {
+        max_renewals: 5,
+        allow_overdue: true,
+    });
+    let record = library.renew(loan, "2023-04-25").unwrap();
+    assert_eq!(record.due_date, date("2023-05-16"));
+
+    // 有人排队时不能续借，预约取消后可以
+    let hold = library
+        .place_hold(bob, "Rust Programming", "2023-04-26")
+        .unwrap()
+        .id;
+    assert_eq!(
+        library.renew(loan, "2023-04-27").unwrap_err(),
+        LibraryError::HoldPending {
+            title: "Rust Programming".into()
+        }
+    );
+    library.cancel_hold(hold, "2023-04-28").unwrap();
+    library.renew(loan, "2023-04-28").unwrap();
+
+    // 延长后超出日期范围时续借失败，应还日期不变
+    library.set_loan_policy(LoanPolicy::new(u32::MAX));
+    assert_eq!(
+        library.renew(loan, "2023-04-29").unwrap_err(),
+        LibraryError::DateOutOfRange {
+            date: date("2023-06-06"),
+            days: u32::MAX
+        }
+    );
+    assert_eq!(library.loan(loan).unwrap().due_date, date("2023-06-06"));
+
+    library.suspend_member(alice).unwrap();
+    assert_eq!(
+        library.renew(loan, "2023-04-29").unwrap_err(),
+        LibraryError::MemberSuspended { id: alice }
+    );
+    assert_eq!(library.loan(loan).unwrap().renewals.len(), 2);
+}
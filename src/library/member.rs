@@ -0,0 +1,196 @@
+// 读者：登记后才能借书，被停借的读者不能再借，但仍可以还书
+#[cfg(test)]
+use super::Author;
+use super::{BorrowRecord, Library, LibraryError};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+#[cfg(test)]
+use std::rc::Rc;
+
+// 读者编号，登记时按顺序分配，从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MemberId(pub(super) u32);
+
+impl MemberId {
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for MemberId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "M{:04}", self.0)
+    }
+}
+
+// 读者的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MemberStatus {
+    Active,    // 正常
+    Suspended, // 停借
+}
+
+impl fmt::Display for MemberStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MemberStatus::Active => "active",
+            MemberStatus::Suspended => "suspended",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Member {
+    pub id: MemberId,
+    pub name: String,
+    pub contact: String, // 联系方式，电话或邮箱
+    pub status: MemberStatus,
+}
+
+impl Library {
+    // 登记新读者，返回登记好的读者
+    pub fn register_member(
+        &mut self,
+        name: impl Into<String>,
+        contact: impl Into<String>,
+    ) -> &Member {
+        let id = MemberId(self.last_ids.member + 1);
+        self.last_ids.member = id.0;
+        self.members.push(Member {
+            id,
+            name: name.into(),
+            contact: contact.into(),
+            status: MemberStatus::Active,
+        });
+        self.members.last().unwrap()
+    }
+
+    pub fn member(&self, id: MemberId) -> Option<&Member> {
+        self.members.iter().find(|member| member.id == id)
+    }
+
+    // 停借，已借出的书不受影响
+    pub fn suspend_member(&mut self, id: MemberId) -> Result<&Member, LibraryError> {
+        self.set_member_status(id, MemberStatus::Suspended)
+    }
+
+    // 恢复借阅
+    pub fn reinstate_member(&mut self, id: MemberId) -> Result<&Member, LibraryError> {
+        self.set_member_status(id, MemberStatus::Active)
+    }
+
+    // 读者当前借着、尚未归还的书
+    pub fn loans_for(&self, id: MemberId) -> Result<Vec<&BorrowRecord>, LibraryError> {
+        Ok(self
+            .borrowing_history(id)?
+            .into_iter()
+            .filter(|record| record.return_date.is_none())
+            .collect())
+    }
+
+    // 读者的全部借阅记录，按借书的先后排列
+    pub fn borrowing_history(&self, id: MemberId) -> Result<Vec<&BorrowRecord>, LibraryError> {
+        self.find_member(id)?;
+        Ok(self
+            .borrow_records
+            .iter()
+            .filter(|record| record.borrower == id)
+            .collect())
+    }
+
+    fn set_member_status(
+        &mut self,
+        id: MemberId,
+        status: MemberStatus,
+    ) -> Result<&Member, LibraryError> {
+        let member = self
+            .members
+            .iter_mut()
+            .find(|member| member.id == id)
+            .ok_or(LibraryError::UnknownMember { id })?;
+        member.status = status;
+        Ok(member)
+    }
+
+    fn find_member(&self, id: MemberId) -> Result<&Member, LibraryError> {
+        self.member(id).ok_or(LibraryError::UnknownMember { id })
+    }
+
+    // 借书前的检查：读者必须已登记且没有被停借
+    pub(super) fn check_borrower(&self, id: MemberId) -> Result<(), LibraryError> {
+        match self.find_member(id)?.status {
+            MemberStatus::Active => Ok(()),
+            MemberStatus::Suspended => Err(LibraryError::MemberSuspended { id }),
+        }
+    }
+}
+
+#[test]
+fn test_members() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library
+        .add_book("Rust Programming".into(), Rc::clone(&author))
+        .unwrap();
+    library.add_book("The Rust Book".into(), author).unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    assert_eq!(alice.to_string(), "M0001");
+    assert_eq!(library.member(bob).unwrap().name, "Bob");
+
+    library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap();
+    library
+        .return_book("Rust Programming", "2023-04-10")
+        .unwrap();
+    library
+        .borrow_book(bob, "Rust Programming", "2023-04-11")
+        .unwrap();
+    library
+        .borrow_book(alice, "The Rust Book", "2023-04-12")
+        .unwrap();
+
+    let titles = |records: Vec<&BorrowRecord>| -> Vec<String> {
+        records.iter().map(|r| r.book.title.clone()).collect()
+    };
+    assert_eq!(
+        titles(library.borrowing_history(alice).unwrap()),
+        ["Rust Programming", "The Rust Book"]
+    );
+    assert_eq!(titles(library.loans_for(alice).unwrap()), ["The Rust Book"]);
+    assert_eq!(
+        titles(library.loans_for(bob).unwrap()),
+        ["Rust Programming"]
+    );
+
+    // 停借后不能再借书，但可以还书；恢复后照常借阅
+    library.suspend_member(alice).unwrap();
+    library.return_book("The Rust Book", "2023-04-20").unwrap();
+    assert_eq!(
+        library
+            .borrow_book(alice, "The Rust Book", "2023-04-21")
+            .unwrap_err(),
+        LibraryError::MemberSuspended { id: alice }
+    );
+    assert_eq!(
+        library.reinstate_member(alice).unwrap().status,
+        MemberStatus::Active
+    );
+    library
+        .borrow_book(alice, "The Rust Book", "2023-04-21")
+        .unwrap();
+
+    let stranger = MemberId(99);
+    assert_eq!(
+        library.loans_for(stranger).unwrap_err(),
+        LibraryError::UnknownMember { id: stranger }
+    );
+    assert!(library
+        .borrow_book(stranger, "The Rust Book", "2023-04-21")
+        .is_err());
+}
@@ -0,0 +1,341 @@
+// 书籍的书目信息：出版社、出版年份、版次、语种、页数、体裁和主题标签，都可以没有。
+// 用 BookBuilder 构造带完整信息的书籍，用 BookFilter 按这些信息筛选书籍
+use super::{AuthorId, Book, Isbn, Library, LibraryError, Role, DEFAULT_CATEGORY};
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookMetadata {
+    pub publisher: Option<String>,
+    pub year: Option<i32>,        // 出版年份
+    pub edition: Option<u32>,     // 第几版
+    pub language: Option<String>, // 语种代码，如 "zh"、"en"
+    pub pages: Option<u32>,
+    pub genres: Vec<String>, // 体裁，如 "programming"
+    pub tags: Vec<String>,   // 主题标签
+}
+
+impl BookMetadata {
+    // 语种、体裁和标签比较时不区分大小写
+    pub fn has_language(&self, language: &str) -> bool {
+        self.language
+            .as_deref()
+            .is_some_and(|l| l.eq_ignore_ascii_case(language))
+    }
+
+    pub fn has_genre(&self, genre: &str) -> bool {
+        self.genres.iter().any(|g| g.eq_ignore_ascii_case(genre))
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+}
+
+// 逐项设置书籍信息，最后用 build 加入图书馆。至少要有一位作者、编者或译者
+#[derive(Debug, Clone)]
+pub struct BookBuilder {
+    title: String,
+    contributors: Vec<(AuthorId, Role)>,
+    category: String,
+    isbn: Option<Isbn>,
+    metadata: BookMetadata,
+}
+
+impl BookBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        BookBuilder {
+            title: title.into(),
+            contributors: Vec::new(),
+            category: DEFAULT_CATEGORY.to_string(),
+            isbn: None,
+            metadata: BookMetadata::default(),
+        }
+    }
+
+    // 贡献者按添加的顺序排列
+    pub fn with_contributor(mut self, author: AuthorId, role: Role) -> Self {
+        self.contributors.push((author, role));
+        self
+    }
+
+    pub fn with_author(self, author: AuthorId) -> Self {
+        self.with_contributor(author, Role::Author)
+    }
+
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = category.into();
+        self
+    }
+
+    pub fn with_isbn(mut self, isbn: Isbn) -> Self {
+        self.isbn = Some(isbn);
+        self
+    }
+
+    pub fn with_publisher(mut self, publisher: impl Into<String>) -> Self {
+        self.metadata.publisher = Some(publisher.into());
+        self
+    }
+
+    pub fn with_year(mut self, year: i32) -> Self {
+        self.metadata.year = Some(year);
+        self
+    }
+
+    pub fn with_edition(mut self, edition: u32) -> Self {
+        self.metadata.edition = Some(edition);
+        self
+    }
+
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.metadata.language = Some(language.into());
+        self
+    }
+
+    pub fn with_pages(mut self, pages: u32) -> Self {
+        self.metadata.pages = Some(pages);
+        self
+    }
+
+    pub fn with_genre(mut self, genre: impl Into<String>) -> Self {
+        self.metadata.genres.push(genre.into());
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.metadata.tags.push(tag.into());
+        self
+    }
+
+    // 把书加入图书馆，同时入藏一本自动编号的副本
+    pub fn build(self, library: &mut Library) -> Result<Rc<Book>, LibraryError> {
+        let contributors = library.contributors(&self.contributors)?;
+        if contributors.is_empty() {
+            return Err(LibraryError::NoContributors { title: self.title });
+        }
+        library.insert_book(
+            self.title,
+            contributors,
+            self.category,
+            self.isbn,
+            self.metadata,
+        )
+    }
+}
+
+// 书籍的筛选条件，所有设置的条件都满足才算匹配，没有设置条件时匹配所有书。
+// 按年份筛选时没有出版年份的书不匹配
+#[derive(Debug, Clone, Default)]
+pub struct BookFilter {
+    language: Option<String>,
+    genre: Option<String>,
+    tag: Option<String>,
+    publisher: Option<String>,
+    category: Option<String>,
+    author: Option<AuthorId>,
+    after: Option<i32>,  // 出版年份大于它
+    before: Option<i32>, // 出版年份小于它
+}
+
+impl BookFilter {
+    pub fn new() -> Self {
+        BookFilter::default()
+    }
+
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    pub fn genre(mut self, genre: impl Into<String>) -> Self {
+        self.genre = Some(genre.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    // 出版社不区分大小写
+    pub fn publisher(mut self, publisher: impl Into<String>) -> Self {
+        self.publisher = Some(publisher.into());
+        self
+    }
+
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    // 作者以任何身份参与的书
+    pub fn author(mut self, author: AuthorId) -> Self {
+        self.author = Some(author);
+        self
+    }
+
+    // 在 year 之后（不含 year）出版
+    pub fn published_after(mut self, year: i32) -> Self {
+        self.after = Some(year);
+        self
+    }
+
+    // 在 year 之前（不含 year）出版
+    pub fn published_before(mut self, year: i32) -> Self {
+        self.before = Some(year);
+        self
+    }
+
+    fn matches(&self, library: &Library, book: &Book) -> bool {
+        let metadata = &book.metadata;
+        let year_ok = |bound: Option<i32>, ok: fn(i32, i32) -> bool| match bound {
+            None => true,
+            Some(bound) => metadata.year.is_some_and(|year| ok(year, bound)),
+        };
+        self.language
+            .as_deref()
+            .map_or(true, |l| metadata.has_language(l))
+            && self
+                .genre
+                .as_deref()
+                .map_or(true, |g| metadata.has_genre(g))
+            && self.tag.as_deref().map_or(true, |t| metadata.has_tag(t))
+            && self.publisher.as_deref().map_or(true, |p| {
+                metadata
+                    .publisher
+                    .as_deref()
+                    .is_some_and(|publisher| publisher.eq_ignore_ascii_case(p))
+            })
+            && self
+                .category
+                .as_deref()
+                .map_or(true, |c| book.category == c)
+            && self.author.map_or(true, |id| {
+                library.author(id).is_some_and(|author| {
+                    book.contributors
+                        .iter()
+                        .any(|c| Rc::ptr_eq(&c.author, author))
+                })
+            })
+            && year_ok(self.after, |year, bound| year > bound)
+            && year_ok(self.before, |year, bound| year < bound)
+    }
+}
+
+impl Library {
+    // 满足筛选条件的书，按入藏顺序排列
+    pub fn filter_books(&self, filter: &BookFilter) -> Vec<Rc<Book>> {
+        self.books
+            .iter()
+            .filter(|book| filter.matches(self, book))
+            .cloned()
+            .collect()
+    }
+}
+
+#[test]
+fn test_book_builder() {
+    let mut library = Library::new();
+    let klabnik = library.register_author("Steve Klabnik", 1980);
+    let translator = library.register_author("张三", 1975);
+    let isbn = Isbn::parse("978-1-7185-0044-0").unwrap();
+    let book = BookBuilder::new("The Rust Book")
+        .with_author(klabnik)
+        .with_contributor(translator, Role::Translator)
+        .with_isbn(isbn)
+        .with_category("reference")
+        .with_publisher("No Starch Press")
+        .with_year(2023)
+        .with_edition(2)
+        .with_language("en")
+        .with_pages(560)
+        .with_genre("programming")
+        .with_tag("rust")
+        .build(&mut library)
+        .unwrap();
+    assert_eq!(book.isbn, Some(isbn));
+    assert_eq!(book.category, "reference");
+    assert_eq!(book.contributors.len(), 2);
+    assert_eq!(
+        book.metadata,
+        BookMetadata {
+            publisher: Some("No Starch Press".into()),
+            year: Some(2023),
+            edition: Some(2),
+            language: Some("en".into()),
+            pages: Some(560),
+            genres: vec!["programming".into()],
+            tags: vec!["rust".into()],
+        }
+    );
+    assert_eq!(library.available_copies(isbn), Ok(1));
+
+    // 旧的添加方式得到空的书目信息
+    let author = Rc::clone(library.author(klabnik).unwrap());
+    let plain = library.add_book("Rust Programming".into(), author).unwrap();
+    assert_eq!(plain.metadata, BookMetadata::default());
+
+    assert_eq!(
+        BookBuilder::new("Anonymous")
+            .build(&mut library)
+            .unwrap_err(),
+        LibraryError::NoContributors {
+            title: "Anonymous".into()
+        }
+    );
+    assert_eq!(
+        BookBuilder::new("Copy")
+            .with_author(klabnik)
+            .with_isbn(isbn)
+            .build(&mut library)
+            .unwrap_err(),
+        LibraryError::DuplicateIsbn { isbn }
+    );
+}
+
+#[test]
+fn test_filter_books() {
+    let mut library = Library::new();
+    let zhang = library.register_author("张汉东", 1985);
+    let klabnik = library.register_author("Steve Klabnik", 1980);
+    let books = [
+        ("Rust 编程之道", zhang, "zh", Some(2019), "programming"),
+        ("深入浅出 Rust", zhang, "ZH", Some(2018), "programming"),
+        ("旧书", zhang, "zh", Some(2010), "programming"),
+        ("无年份", zhang, "zh", None, "programming"),
+        ("Rust 诗集", zhang, "zh", Some(2020), "poetry"),
+        ("The Rust Book", klabnik, "en", Some(2019), "programming"),
+    ];
+    for (title, author, language, year, genre) in books {
+        let mut builder = BookBuilder::new(title)
+            .with_author(author)
+            .with_language(language)
+            .with_genre(genre);
+        if let Some(year) = year {
+            builder = builder.with_year(year);
+        }
+        builder.build(&mut library).unwrap();
+    }
+    let titles = |filter: BookFilter| -> Vec<String> {
+        library
+            .filter_books(&filter)
+            .iter()
+            .map(|b| b.title.clone())
+            .collect()
+    };
+
+    // 2015 年以后出版的中文编程书；语种不区分大小写，没有年份的书不匹配
+    let filter = BookFilter::new()
+        .language("zh")
+        .published_after(2015)
+        .genre("Programming");
+    assert_eq!(titles(filter), ["Rust 编程之道", "深入浅出 Rust"]);
+    assert_eq!(
+        titles(BookFilter::new().published_before(2019)),
+        ["深入浅出 Rust", "旧书"]
+    );
+    assert_eq!(titles(BookFilter::new().author(klabnik)), ["The Rust Book"]);
+    assert_eq!(library.filter_books(&BookFilter::new()).len(), 6);
+    assert!(titles(BookFilter::new().tag("rust")).is_empty());
+}
@@ -0,0 +1,962 @@
+// 图书馆的 JSON 存档。
+//
+// 内存里作者、书籍通过 Rc 共享，存档时作者和书籍都使用自己的编号，
+// 书籍通过作者编号、副本通过书籍编号、借阅记录通过副本条码、预约通过书籍编号引用，读档时按编号重建同样的共享关系。
+// 存档带有格式版本号，以后格式变化时按版本号迁移旧存档
+#[cfg(test)]
+use super::BookBuilder;
+use super::{
+    primary_author, Author, AuthorId, Book, BookCopy, BookId, BookMetadata, BorrowRecord,
+    Contributor, CopyStatus, FinePolicy, Hold, HoldId, HoldStatus, Isbn, Library, LibraryError,
+    LoanId, LoanPolicy, Member, MemberId, MemberStatus, Renewal, RenewalPolicy, Role,
+};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::{error, fmt};
+
+// 当前的存档格式版本。格式发布后再有变化时递增，并为上一版本的存档补上迁移
+pub const SCHEMA_VERSION: u32 = 1;
+
+// 存档、读档可能出现的错误
+#[derive(Debug)]
+pub enum PersistError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    // 存档的版本不是当前程序支持的版本
+    UnsupportedVersion { found: u32, supported: u32 },
+    // 同一类实体出现了重复的编号
+    DuplicateId { kind: &'static str, id: String },
+    // 引用了不存在的编号
+    UnknownId { kind: &'static str, id: String },
+    // 书籍没有任何作者、编者或译者
+    NoContributors { book: u32 },
+    // 书籍引用的作者实例没有登记，无法写出作者编号
+    UnregisteredAuthor { name: String },
+    // 已留出副本的预约缺少副本条码或取书期限
+    IncompleteHold { hold: HoldId },
+    // 副本状态与借阅记录、预约对不上，如副本在架却有未归还的借阅记录
+    InconsistentCopy { barcode: String, reason: String },
+    // 图书馆拒绝了存档中的数据
+    Rejected(LibraryError),
+}
+
+impl fmt::Display for PersistError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PersistError::Io(e) => write!(f, "i/o error: {}", e),
+            PersistError::Json(e) => write!(f, "malformed library file: {}", e),
+            PersistError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "library file version {} is not supported (supported version {})",
+                found, supported
+            ),
+            PersistError::DuplicateId { kind, id } => write!(f, "duplicate {} id {}", kind, id),
+            PersistError::UnknownId { kind, id } => write!(f, "unknown {} id {}", kind, id),
+            PersistError::NoContributors { book } => {
+                write!(f, "book id {} has no contributors", book)
+            }
+            PersistError::UnregisteredAuthor { name } => {
+                write!(f, "author '{}' is not registered", name)
+            }
+            PersistError::IncompleteHold { hold } => {
+                write!(f, "ready hold {} has no copy or pickup deadline", hold)
+            }
+            PersistError::InconsistentCopy { barcode, reason } => {
+                write!(f, "copy {} {}", barcode, reason)
+            }
+            PersistError::Rejected(e) => e.fmt(f),
+        }
+    }
+}
+
+impl error::Error for PersistError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            PersistError::Io(e) => Some(e),
+            PersistError::Json(e) => Some(e),
+            PersistError::Rejected(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PersistError {
+    fn from(e: io::Error) -> Self {
+        PersistError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for PersistError {
+    fn from(e: serde_json::Error) -> Self {
+        PersistError::Json(e)
+    }
+}
+
+// 存档文件的结构
+#[derive(Serialize, Deserialize)]
+struct LibraryFile {
+    version: u32,
+    loan_policy: LoanPolicy,
+    fine_policy: FinePolicy,
+    renewal_policy: RenewalPolicy,
+    pickup_days: u32,
+    authors: Vec<AuthorEntry>,
+    books: Vec<BookEntry>,
+    copies: Vec<CopyEntry>,
+    members: Vec<MemberEntry>,
+    borrow_records: Vec<RecordEntry>,
+    holds: Vec<HoldEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AuthorEntry {
+    id: u32,
+    name: String,
+    birth_year: i32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BookEntry {
+    id: u32,
+    title: String,
+    contributors: Vec<ContributorEntry>,
+    category: String,
+    isbn: Option<Isbn>,
+    metadata: BookMetadata,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ContributorEntry {
+    author: u32,
+    role: Role,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CopyEntry {
+    barcode: String,
+    book: u32,
+    status: CopyStatus,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MemberEntry {
+    id: MemberId,
+    name: String,
+    contact: String,
+    status: MemberStatus,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordEntry {
+    id: LoanId,
+    barcode: String,
+    borrower: MemberId,
+    borrowed_date: NaiveDate,
+    due_date: NaiveDate,
+    return_date: Option<NaiveDate>,
+    fine: u64,
+    renewals: Vec<Renewal>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HoldEntry {
+    id: HoldId,
+    book: u32,
+    member: MemberId,
+    placed_date: NaiveDate,
+    status: HoldStatus,
+    barcode: Option<String>,
+    expires: Option<NaiveDate>,
+}
+
+// 只读出版本号，先确认是支持的格式再完整解析
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+impl Library {
+    // 以 JSON 格式保存到文件，已存在的文件会被覆盖
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PersistError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut writer, &self.to_file()?)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    // 从 save 生成的文件读取图书馆
+    pub fn load(path: impl AsRef<Path>) -> Result<Library, PersistError> {
+        let json = io::read_to_string(BufReader::new(File::open(path)?))?;
+        Library::from_json(&json)
+    }
+
+    fn from_json(json: &str) -> Result<Library, PersistError> {
+        let Header { version } = serde_json::from_str(json)?;
+        if version != SCHEMA_VERSION {
+            return Err(PersistError::UnsupportedVersion {
+                found: version,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        let file: LibraryFile = serde_json::from_str(json)?;
+        Library::from_file(file)
+    }
+
+    fn to_file(&self) -> Result<LibraryFile, PersistError> {
+        let authors = self
+            .authors
+            .iter()
+            .map(|(id, author)| AuthorEntry {
+                id: id.0,
+                name: author.name.clone(),
+                birth_year: author.birth_year,
+            })
+            .collect();
+        let books = self
+            .books
+            .iter()
+            .map(|book| {
+                let contributors = book
+                    .contributors
+                    .iter()
+                    .map(|c| {
+                        // 按登记的实例取编号，同名同年的作者不会混为一人
+                        let author = self.authors.id_of_entry(&c.author).ok_or_else(|| {
+                            PersistError::UnregisteredAuthor {
+                                name: c.author.name.clone(),
+                            }
+                        })?;
+                        Ok(ContributorEntry {
+                            author: author.0,
+                            role: c.role,
+                        })
+                    })
+                    .collect::<Result<_, PersistError>>()?;
+                Ok(BookEntry {
+                    id: book.id.0,
+                    title: book.title.clone(),
+                    contributors,
+                    category: book.category.clone(),
+                    isbn: book.isbn,
+                    metadata: book.metadata.clone(),
+                })
+            })
+            .collect::<Result<_, PersistError>>()?;
+        let copies = self
+            .copies
+            .iter()
+            .map(|copy| CopyEntry {
+                barcode: copy.barcode.clone(),
+                book: copy.book.id.0,
+                status: copy.status,
+            })
+            .collect();
+        let members = self
+            .members
+            .iter()
+            .map(|member| MemberEntry {
+                id: member.id,
+                name: member.name.clone(),
+                contact: member.contact.clone(),
+                status: member.status,
+            })
+            .collect();
+        let borrow_records = self
+            .borrow_records
+            .iter()
+            .map(|record| RecordEntry {
+                id: record.id,
+                barcode: record.barcode.clone(),
+                borrower: record.borrower,
+                borrowed_date: record.borrowed_date,
+                due_date: record.due_date,
+                return_date: record.return_date,
+                fine: record.fine,
+                renewals: record.renewals.clone(),
+            })
+            .collect();
+        let holds = self
+            .holds
+            .iter()
+            .map(|hold| HoldEntry {
+                id: hold.id,
+                book: hold.book.id.0,
+                member: hold.member,
+                placed_date: hold.placed_date,
+                status: hold.status,
+                barcode: hold.barcode.clone(),
+                expires: hold.expires,
+            })
+            .collect();
+
+        Ok(LibraryFile {
+            version: SCHEMA_VERSION,
+            loan_policy: self.loan_policy.clone(),
+            fine_policy: self.fine_policy,
+            renewal_policy: self.renewal_policy,
+            pickup_days: self.pickup_days,
+            authors,
+            books,
+            copies,
+            members,
+            borrow_records,
+            holds,
+        })
+    }
+
+    fn from_file(file: LibraryFile) -> Result<Library, PersistError> {
+        let mut library = Library::new();
+        library.loan_policy = file.loan_policy;
+        library.fine_policy = file.fine_policy;
+        library.renewal_policy = file.renewal_policy;
+        library.pickup_days = file.pickup_days;
+        for entry in file.authors {
+            let author = Rc::new(Author {
+                name: entry.name,
+                birth_year: entry.birth_year,
+            });
+            if !library.authors.insert(AuthorId(entry.id), author) {
+                return Err(PersistError::DuplicateId {
+                    kind: "author",
+                    id: entry.id.to_string(),
+                });
+            }
+        }
+
+        let mut books = HashMap::new();
+        for entry in file.books {
+            let contributors = entry
+                .contributors
+                .iter()
+                .map(|c| {
+                    let author =
+                        library
+                            .author(AuthorId(c.author))
+                            .ok_or(PersistError::UnknownId {
+                                kind: "author",
+                                id: c.author.to_string(),
+                            })?;
+                    Ok(Contributor {
+                        author: Rc::clone(author),
+                        role: c.role,
+                    })
+                })
+                .collect::<Result<Vec<_>, PersistError>>()?;
+            if contributors.is_empty() {
+                return Err(PersistError::NoContributors { book: entry.id });
+            }
+            if books.contains_key(&entry.id) {
+                return Err(PersistError::DuplicateId {
+                    kind: "book",
+                    id: entry.id.to_string(),
+                });
+            }
+            let book = library
+                .push_book(Book {
+                    id: BookId(entry.id),
+                    isbn: entry.isbn,
+                    title: entry.title,
+                    author: primary_author(&contributors),
+                    contributors,
+                    category: entry.category,
+                    metadata: entry.metadata,
+                })
+                .map_err(|e| match e {
+                    LibraryError::DuplicateIsbn { isbn } => PersistError::DuplicateId {
+                        kind: "isbn",
+                        id: isbn.to_string(),
+                    },
+                    e => PersistError::Rejected(e),
+                })?;
+            books.insert(entry.id, book);
+        }
+
+        for entry in file.copies {
+            let book = books.get(&entry.book).ok_or(PersistError::UnknownId {
+                kind: "book",
+                id: entry.book.to_string(),
+            })?;
+            if library.copy(&entry.barcode).is_some() {
+                return Err(PersistError::DuplicateId {
+                    kind: "copy",
+                    id: entry.barcode,
+                });
+            }
+            library.copies.push(BookCopy {
+                barcode: entry.barcode,
+                book: Rc::clone(book),
+                status: entry.status,
+            });
+        }
+
+        for entry in file.members {
+            if library.member(entry.id).is_some() {
+                return Err(PersistError::DuplicateId {
+                    kind: "member",
+                    id: entry.id.to_string(),
+                });
+            }
+            library.last_ids.member = library.last_ids.member.max(entry.id.0);
+            library.members.push(Member {
+                id: entry.id,
+                name: entry.name,
+                contact: entry.contact,
+                status: entry.status,
+            });
+        }
+
+        for entry in file.borrow_records {
+            if library.loan(entry.id).is_some() {
+                return Err(PersistError::DuplicateId {
+                    kind: "loan",
+                    id: entry.id.to_string(),
+                });
+            }
+            if library.member(entry.borrower).is_none() {
+                return Err(PersistError::UnknownId {
+                    kind: "member",
+                    id: entry.borrower.to_string(),
+                });
+            }
+            let copy = library
+                .copy(&entry.barcode)
+                .ok_or_else(|| PersistError::UnknownId {
+                    kind: "copy",
+                    id: entry.barcode.clone(),
+                })?;
+            let record = BorrowRecord {
+                id: entry.id,
+                book: Rc::clone(&copy.book),
+                barcode: entry.barcode,
+                borrower: entry.borrower,
+                borrowed_date: entry.borrowed_date,
+                due_date: entry.due_date,
+                return_date: entry.return_date,
+                fine: entry.fine,
+                renewals: entry.renewals,
+            };
+            library.last_ids.loan = library.last_ids.loan.max(entry.id.0);
+            library.borrow_records.push(record);
+        }
+
+        for entry in file.holds {
+            if library.hold(entry.id).is_some() {
+                return Err(PersistError::DuplicateId {
+                    kind: "hold",
+                    id: entry.id.to_string(),
+                });
+            }
+            if library.member(entry.member).is_none() {
+                return Err(PersistError::UnknownId {
+                    kind: "member",
+                    id: entry.member.to_string(),
+                });
+            }
+            let book = books.get(&entry.book).ok_or(PersistError::UnknownId {
+                kind: "book",
+                id: entry.book.to_string(),
+            })?;
+            if let Some(barcode) = &entry.barcode {
+                if library.copy(barcode).is_none() {
+                    return Err(PersistError::UnknownId {
+                        kind: "copy",
+                        id: barcode.clone(),
+                    });
+                }
+            }
+            library.last_ids.hold = library.last_ids.hold.max(entry.id.0);
+            library.holds.push(Hold {
+                id: entry.id,
+                book: Rc::clone(book),
+                member: entry.member,
+                placed_date: entry.placed_date,
+                status: entry.status,
+                barcode: entry.barcode,
+                expires: entry.expires,
+            });
+        }
+        library.check_circulation()?;
+        Ok(library)
+    }
+
+    // 副本状态、未归还的借阅记录和已留出副本的预约必须互相吻合，
+    // 否则读档后借书时会把同一副本借出两次，或者找不到留给预约的副本
+    fn check_circulation(&self) -> Result<(), PersistError> {
+        let inconsistent = |barcode: &str, reason: String| PersistError::InconsistentCopy {
+            barcode: barcode.to_string(),
+            reason,
+        };
+        let mut open_loans = HashMap::new();
+        for record in &self.borrow_records {
+            if record.return_date.is_none()
+                && open_loans
+                    .insert(record.barcode.as_str(), record.id)
+                    .is_some()
+            {
+                return Err(inconsistent(
+                    &record.barcode,
+                    "has more than one open loan".to_string(),
+                ));
+            }
+        }
+        let mut ready_holds = HashMap::new();
+        for hold in &self.holds {
+            if hold.status != HoldStatus::Ready {
+                continue;
+            }
+            let barcode = match (&hold.barcode, hold.expires) {
+                (Some(barcode), Some(_)) => barcode,
+                _ => return Err(PersistError::IncompleteHold { hold: hold.id }),
+            };
+            if ready_holds.insert(barcode.as_str(), hold.id).is_some() {
+                return Err(inconsistent(
+                    barcode,
+                    "is held for more than one ready hold".to_string(),
+                ));
+            }
+        }
+        for copy in &self.copies {
+            let barcode = copy.barcode.as_str();
+            let on_loan = open_loans.contains_key(barcode);
+            let on_hold = ready_holds.contains_key(barcode);
+            let status = copy.status;
+            // 借出后遗失的副本仍然挂着未归还的借阅记录
+            let loan_matches = match status {
+                CopyStatus::OnLoan => on_loan,
+                CopyStatus::Lost => true,
+                _ => !on_loan,
+            };
+            let hold_matches = (status == CopyStatus::OnHold) == on_hold;
+            let reason = if !loan_matches && on_loan {
+                format!("has an open loan but is {}", status)
+            } else if !loan_matches {
+                "is on loan without an open loan".to_string()
+            } else if !hold_matches && on_hold {
+                format!("is held for a ready hold but is {}", status)
+            } else if !hold_matches {
+                "is on hold without a ready hold".to_string()
+            } else {
+                continue;
+            };
+            return Err(inconsistent(barcode, reason));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    let klabnik = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let blandy = Rc::new(Author {
+        name: "Jim Blandy".into(),
+        birth_year: 1965,
+    });
+    let mut library = Library::new();
+    library
+        .add_book("Rust Programming".into(), Rc::clone(&klabnik))
+        .unwrap();
+    library
+        .add_book("The Rust Book".into(), Rc::clone(&klabnik))
+        .unwrap();
+    library
+        .add_book_in_category("Programming Rust".into(), blandy, "reference")
+        .unwrap();
+    library.set_loan_policy(LoanPolicy::new(14).with_category("reference", 3));
+    library.set_fine_policy(FinePolicy {
+        daily_rate: 10,
+        grace_days: 0,
+        cap: Some(200),
+    });
+    library
+        .add_copy("Rust Programming", "RP-2", "2023-03-31")
+        .unwrap();
+    library
+        .set_copy_status("RP-2", CopyStatus::InRepair, "2023-03-31")
+        .unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap();
+    library
+        .return_book("Rust Programming", "2023-04-30")
+        .unwrap();
+    library
+        .borrow_book(alice, "Programming Rust", "2023-04-01")
+        .unwrap();
+    let bob_loan = library
+        .borrow_book(bob, "Rust Programming", "2023-05-02")
+        .unwrap()
+        .id;
+    library.set_renewal_policy(RenewalPolicy {
+        max_renewals: 1,
+        allow_overdue: true,
+    });
+    library.renew(bob_loan, "2023-05-10").unwrap();
+    library.suspend_member(bob).unwrap();
+    library.set_pickup_days(5);
+    let carol = library.register_member("Carol", "").id;
+    let hold = library
+        .place_hold(carol, "Programming Rust", "2023-04-02")
+        .unwrap()
+        .id;
+
+    let path = std::env::temp_dir().join(format!("rstut-library-{}.json", std::process::id()));
+    library.save(&path).unwrap();
+    let loaded = Library::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    let loaded = loaded.unwrap();
+
+    let titles: Vec<_> = loaded.books.iter().map(|b| b.title.as_str()).collect();
+    assert_eq!(
+        titles,
+        ["Rust Programming", "The Rust Book", "Programming Rust"]
+    );
+    // 共享关系保持不变：同一作者的书指向同一个作者实例，借阅记录指向书籍列表中的实例
+    assert!(Rc::ptr_eq(&loaded.books[0].author, &loaded.books[1].author));
+    assert!(!Rc::ptr_eq(
+        &loaded.books[0].author,
+        &loaded.books[2].author
+    ));
+    let klabnik = loaded.authors().id_of(&klabnik).unwrap();
+    assert!(Rc::ptr_eq(
+        &loaded.books[0].author,
+        loaded.author(klabnik).unwrap()
+    ));
+    assert_eq!(loaded.authors().len(), 2);
+    assert_eq!(loaded.borrow_records.len(), 3);
+    for record in [&loaded.borrow_records[0], &loaded.borrow_records[2]] {
+        assert!(Rc::ptr_eq(&record.book, &loaded.books[0]));
+        assert_eq!(record.barcode, "C000001");
+    }
+    // 分类、应还日期、罚款和规则本身都能读回
+    assert_eq!(loaded.books[2].category, "reference");
+    assert_eq!(loaded.borrow_records[0].fine, 150);
+    assert_eq!(
+        loaded.borrow_records[1].due_date,
+        NaiveDate::from_ymd_opt(2023, 4, 4).unwrap()
+    );
+    assert_eq!(loaded.loan_policy(), library.loan_policy());
+    assert_eq!(loaded.fine_policy(), library.fine_policy());
+    assert_eq!(loaded.renewal_policy(), library.renewal_policy());
+    let bob_record = loaded.loan(bob_loan).unwrap();
+    assert_eq!(bob_record.borrower, bob);
+    assert_eq!(
+        bob_record.renewals,
+        library.loan(bob_loan).unwrap().renewals
+    );
+    let copies: Vec<_> = loaded
+        .copies
+        .iter()
+        .map(|c| (c.barcode.as_str(), c.status))
+        .collect();
+    assert_eq!(
+        copies,
+        [
+            ("C000001", CopyStatus::OnLoan),
+            ("C000002", CopyStatus::Available),
+            ("C000003", CopyStatus::OnLoan),
+            ("RP-2", CopyStatus::InRepair),
+        ]
+    );
+    assert!(Rc::ptr_eq(&loaded.copies[3].book, &loaded.books[0]));
+    assert_eq!(
+        loaded.borrow_records[0].return_date,
+        NaiveDate::from_ymd_opt(2023, 4, 30)
+    );
+    assert_eq!(loaded.borrow_records[2].return_date, None);
+    assert_eq!(loaded.loans_for(bob).unwrap().len(), 1);
+    assert_eq!(loaded.borrowing_history(alice).unwrap().len(), 2);
+    let bob_member = loaded.member(bob).unwrap();
+    assert_eq!(
+        (bob_member.name.as_str(), bob_member.contact.as_str()),
+        ("Bob", "555-0100")
+    );
+    assert_eq!(bob_member.status, MemberStatus::Suspended);
+    assert_eq!(loaded.pickup_days(), 5);
+    let holds = loaded.holds_for("Programming Rust").unwrap();
+    assert_eq!((holds[0].id, holds[0].member), (hold, carol));
+    assert!(Rc::ptr_eq(&holds[0].book, &loaded.books[2]));
+
+    // 读回的图书馆照常工作
+    let mut loaded = loaded;
+    assert!(loaded
+        .borrow_book(alice, "Rust Programming", "2023-05-03")
+        .is_err());
+    loaded
+        .return_book("Rust Programming", "2023-05-10")
+        .unwrap();
+    // 新分配的编号接在存档中最大的编号之后
+    assert_eq!(loaded.register_member("Dave", "").id, MemberId(4));
+    let record = loaded
+        .borrow_book(alice, "Rust Programming", "2023-05-11")
+        .unwrap();
+    assert_eq!(record.id, LoanId(4));
+    // 预约在读回后照常轮到
+    loaded
+        .return_book("Programming Rust", "2023-04-03")
+        .unwrap();
+    assert_eq!(
+        loaded.hold(hold).unwrap().expires,
+        NaiveDate::from_ymd_opt(2023, 4, 8)
+    );
+}
+
+#[test]
+fn test_load_errors() {
+    let err = Library::from_json(r#"{"version": 99, "authors": []}"#).unwrap_err();
+    assert!(matches!(
+        err,
+        PersistError::UnsupportedVersion {
+            found: 99,
+            supported: SCHEMA_VERSION
+        }
+    ));
+
+    // 在一份有效存档上改出错误的引用
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library.add_book("Rust Programming".into(), author).unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap();
+    let valid = serde_json::to_value(library.to_file().unwrap()).unwrap();
+
+    let mut file = valid.clone();
+    file["books"][0]["contributors"][0]["author"] = 9.into();
+    let err = Library::from_json(&file.to_string()).unwrap_err();
+    assert_eq!(err.to_string(), "unknown author id 9");
+
+    let mut file = valid;
+    file["borrow_records"][0]["borrower"] = 7.into();
+    let err = Library::from_json(&file.to_string()).unwrap_err();
+    assert_eq!(err.to_string(), "unknown member id M0007");
+
+    let err = Library::from_json(r#"{"authors": []}"#).unwrap_err();
+    assert!(matches!(err, PersistError::Json(_)));
+    assert!(matches!(
+        Library::load("/nonexistent/library.json"),
+        Err(PersistError::Io(_))
+    ));
+}
+
+#[test]
+fn test_load_inconsistent_circulation() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    for title in ["Rust Programming", "The Rust Book", "Programming Rust"] {
+        library.add_book(title.into(), Rc::clone(&author)).unwrap();
+    }
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    let bob = library.register_member("Bob", "555-0100").id;
+    // C000001 留给 Bob 的预约，C000002 借给了 Alice，C000003 在架
+    library
+        .borrow_book(alice, "Rust Programming", "2023-04-01")
+        .unwrap();
+    library
+        .place_hold(bob, "Rust Programming", "2023-04-02")
+        .unwrap();
+    library
+        .return_book("Rust Programming", "2023-04-10")
+        .unwrap();
+    library
+        .borrow_book(alice, "The Rust Book", "2023-04-10")
+        .unwrap();
+    let valid = serde_json::to_value(library.to_file().unwrap()).unwrap();
+    assert_eq!(valid["holds"][0]["status"], "ready");
+    let load = |edit: &dyn Fn(&mut serde_json::Value)| {
+        let mut file = valid.clone();
+        edit(&mut file);
+        Library::from_json(&file.to_string()).map(|_| ())
+    };
+    let load_err = |edit: &dyn Fn(&mut serde_json::Value)| load(edit).unwrap_err().to_string();
+
+    // 借出后遗失的副本可以挂着未归还的借阅记录
+    load(&|file| file["copies"][1]["status"] = "lost".into()).unwrap();
+
+    // 已留出副本的预约必须有副本条码和取书期限
+    let incomplete = "ready hold H0001 has no copy or pickup deadline";
+    assert_eq!(
+        load_err(&|file| file["holds"][0]["barcode"] = serde_json::Value::Null),
+        incomplete
+    );
+    assert_eq!(
+        load_err(&|file| file["holds"][0]["expires"] = serde_json::Value::Null),
+        incomplete
+    );
+    // 预约指向的副本必须留着
+    assert_eq!(
+        load_err(&|file| file["copies"][0]["status"] = "available".into()),
+        "copy C000001 is held for a ready hold but is available"
+    );
+    assert_eq!(
+        load_err(&|file| file["holds"][0]["barcode"] = "C000003".into()),
+        "copy C000001 is on hold without a ready hold"
+    );
+    // 有未归还借阅记录的副本必须是借出状态，且只能有一条未归还的记录
+    assert_eq!(
+        load_err(&|file| file["copies"][1]["status"] = "available".into()),
+        "copy C000002 has an open loan but is available"
+    );
+    assert_eq!(
+        load_err(&|file| file["copies"][2]["status"] = "on_loan".into()),
+        "copy C000003 is on loan without an open loan"
+    );
+    assert_eq!(
+        load_err(&|file| {
+            let mut record = file["borrow_records"][1].clone();
+            record["id"] = 3.into();
+            file["borrow_records"].as_array_mut().unwrap().push(record);
+        }),
+        "copy C000002 has more than one open loan"
+    );
+}
+
+#[test]
+fn test_round_trip_isbn() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let isbn = Isbn::parse("978-1-7185-0044-0").unwrap();
+    let mut library = Library::new();
+    library
+        .add_book("The Rust Book".into(), Rc::clone(&author))
+        .unwrap();
+    library
+        .add_book_with_isbn("The Rust Book".into(), author, isbn)
+        .unwrap();
+    let json = serde_json::to_string(&library.to_file().unwrap()).unwrap();
+    let loaded = Library::from_json(&json).unwrap();
+    // 编号和 ISBN 原样读回，按 ISBN 查找仍能区分同名的书
+    let book = loaded.book(isbn).unwrap();
+    assert_eq!(book.id, BookId(2));
+    assert_eq!(loaded.book(BookId(1)).unwrap().isbn, None);
+    assert_eq!(loaded.copies_of(isbn).unwrap()[0].barcode, "C000002");
+    // 读回后新入藏的书接着编号
+    let author = Rc::clone(&book.author);
+    let mut loaded = loaded;
+    assert_eq!(
+        loaded
+            .add_book("Rust Programming".into(), author)
+            .unwrap()
+            .id,
+        BookId(3)
+    );
+
+    let json = json.replace(r#""id":1,"title""#, r#""id":2,"title""#);
+    assert_eq!(
+        Library::from_json(&json).unwrap_err().to_string(),
+        "duplicate book id 2"
+    );
+}
+
+#[test]
+fn test_round_trip_contributors() {
+    let mut library = Library::new();
+    let klabnik = library.register_author("Steve Klabnik", 1980);
+    let nichols = library.register_author("Carol Nichols", 1983);
+    library
+        .add_book_with_contributors("Rust Programming".into(), &[(klabnik, Role::Author)])
+        .unwrap();
+
+    // 多位贡献者按顺序存档、读回
+    library
+        .add_book_with_contributors(
+            "The Rust Book".into(),
+            &[(nichols, Role::Editor), (klabnik, Role::Author)],
+        )
+        .unwrap();
+    let json = serde_json::to_string(&library.to_file().unwrap()).unwrap();
+    let loaded = Library::from_json(&json).unwrap();
+    let book = loaded.book("The Rust Book").unwrap();
+    let roles: Vec<_> = book
+        .contributors
+        .iter()
+        .map(|c| (c.author.name.as_str(), c.role))
+        .collect();
+    assert_eq!(
+        roles,
+        [
+            ("Carol Nichols", Role::Editor),
+            ("Steve Klabnik", Role::Author)
+        ]
+    );
+    assert!(Rc::ptr_eq(&book.author, loaded.author(klabnik).unwrap()));
+    assert_eq!(loaded.books_by(klabnik).unwrap().len(), 2);
+
+    let json = json.replace(
+        r#"[{"author":2,"role":"editor"},{"author":1,"role":"author"}]"#,
+        "[]",
+    );
+    assert_eq!(
+        Library::from_json(&json).unwrap_err().to_string(),
+        "book id 2 has no contributors"
+    );
+}
+
+#[test]
+fn test_duplicate_authors() {
+    // 存档里两位作者规范化后的姓名和出生年份相同，各自的书仍然指向各自的编号
+    let mut library = Library::new();
+    let klabnik = library.register_author("Steve Klabnik", 1980);
+    library
+        .add_book_with_contributors("Rust Programming".into(), &[(klabnik, Role::Author)])
+        .unwrap();
+    let mut file = serde_json::to_value(library.to_file().unwrap()).unwrap();
+    let mut author = file["authors"][0].clone();
+    author["id"] = 2.into();
+    author["name"] = "steve  KLABNIK".into();
+    file["authors"].as_array_mut().unwrap().push(author);
+    let mut book = file["books"][0].clone();
+    book["id"] = 2.into();
+    book["title"] = "The Rust Book".into();
+    book["contributors"][0]["author"] = 2.into();
+    file["books"].as_array_mut().unwrap().push(book);
+
+    let loaded = Library::from_json(&file.to_string()).unwrap();
+    let saved = loaded.to_file().unwrap();
+    let authors: Vec<_> = saved
+        .books
+        .iter()
+        .map(|book| book.contributors[0].author)
+        .collect();
+    assert_eq!(authors, [1, 2]);
+    assert_eq!(saved.authors[1].name, "steve  KLABNIK");
+    assert_eq!(loaded.books_by(AuthorId(2)).unwrap().len(), 1);
+}
+
+#[test]
+fn test_round_trip_metadata() {
+    let mut library = Library::new();
+    let zhang = library.register_author("张汉东", 1985);
+    BookBuilder::new("Rust 编程之道")
+        .with_author(zhang)
+        .with_publisher("电子工业出版社")
+        .with_year(2019)
+        .with_language("zh")
+        .with_pages(588)
+        .with_genre("programming")
+        .with_tag("rust")
+        .build(&mut library)
+        .unwrap();
+    let json = serde_json::to_string(&library.to_file().unwrap()).unwrap();
+    let loaded = Library::from_json(&json).unwrap();
+    assert_eq!(loaded.books[0].metadata, library.books[0].metadata);
+    assert_eq!(loaded.books[0].metadata.year, Some(2019));
+}
@@ -0,0 +1,210 @@
+// 借期和罚款规则：借书时按书的分类算出应还日期，还书时按逾期天数算出罚款
+#[cfg(test)]
+use super::Author;
+use super::{BorrowRecord, Library, LibraryError};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(test)]
+use std::rc::Rc;
+
+// 没有指定分类的书
+pub const DEFAULT_CATEGORY: &str = "general";
+
+// 借期规则：每个分类可以有自己的借期，没有单独设置的分类使用默认借期
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoanPolicy {
+    default_days: u32,
+    by_category: BTreeMap<String, u32>,
+}
+
+impl Default for LoanPolicy {
+    // 默认借期 21 天
+    fn default() -> Self {
+        LoanPolicy::new(21)
+    }
+}
+
+impl LoanPolicy {
+    pub fn new(default_days: u32) -> Self {
+        LoanPolicy {
+            default_days,
+            by_category: BTreeMap::new(),
+        }
+    }
+
+    // 为某个分类单独设置借期
+    pub fn with_category(mut self, category: impl Into<String>, days: u32) -> Self {
+        self.by_category.insert(category.into(), days);
+        self
+    }
+
+    pub fn loan_days(&self, category: &str) -> u32 {
+        self.by_category
+            .get(category)
+            .copied()
+            .unwrap_or(self.default_days)
+    }
+
+    // 某个分类的书在 borrowed_date 借出时的应还日期；超出日期范围时返回错误
+    pub fn due_date(
+        &self,
+        category: &str,
+        borrowed_date: NaiveDate,
+    ) -> Result<NaiveDate, LibraryError> {
+        add_days(borrowed_date, self.loan_days(category))
+    }
+}
+
+// date 之后 days 天的日期
+pub(super) fn add_days(date: NaiveDate, days: u32) -> Result<NaiveDate, LibraryError> {
+    date.checked_add_days(Days::new(days.into()))
+        .ok_or(LibraryError::DateOutOfRange { date, days })
+}
+
+// 罚款规则，金额以分为单位。逾期的前 grace_days 天不计罚款，
+// 之后每天 daily_rate，单次借阅的罚款不超过 cap（None 表示不封顶）。默认不罚款
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinePolicy {
+    pub daily_rate: u64,
+    pub grace_days: u32,
+    pub cap: Option<u64>,
+}
+
+impl FinePolicy {
+    // 应还日期为 due_date、实际在 returned 归还的罚款
+    pub fn fine(&self, due_date: NaiveDate, returned: NaiveDate) -> u64 {
+        let late = (returned - due_date).num_days() - i64::from(self.grace_days);
+        if late <= 0 {
+            return 0;
+        }
+        let fine = self.daily_rate.saturating_mul(late as u64);
+        self.cap.map_or(fine, |cap| fine.min(cap))
+    }
+}
+
+impl BorrowRecord {
+    // 到 as_of 为止逾期的天数；已归还的按还书日期计算，没有逾期为 0
+    pub fn days_overdue(&self, as_of: NaiveDate) -> i64 {
+        let end = self.return_date.unwrap_or(as_of);
+        (end - self.due_date).num_days().max(0)
+    }
+}
+
+impl Library {
+    pub fn loan_policy(&self) -> &LoanPolicy {
+        &self.loan_policy
+    }
+
+    // 只影响之后的借阅，已借出的书仍按借出时的应还日期
+    pub fn set_loan_policy(&mut self, policy: LoanPolicy) {
+        self.loan_policy = policy;
+    }
+
+    pub fn fine_policy(&self) -> &FinePolicy {
+        &self.fine_policy
+    }
+
+    // 只影响之后的还书，已经算好的罚款不变
+    pub fn set_fine_policy(&mut self, policy: FinePolicy) {
+        self.fine_policy = policy;
+    }
+
+    // 到 as_of 仍未归还且已过应还日期的借阅，按应还日期排列
+    pub fn overdue_loans(&self, as_of: NaiveDate) -> Vec<&BorrowRecord> {
+        let mut loans: Vec<_> = self
+            .borrow_records
+            .iter()
+            .filter(|record| record.return_date.is_none() && record.due_date < as_of)
+            .collect();
+        loans.sort_by_key(|record| record.due_date);
+        loans
+    }
+}
+
+#[cfg(test)]
+fn date(s: &str) -> NaiveDate {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+}
+
+#[test]
+fn test_fine_policy() {
+    let policy = FinePolicy {
+        daily_rate: 10,
+        grace_days: 2,
+        cap: Some(100),
+    };
+    let due = date("2023-04-10");
+    assert_eq!(policy.fine(due, date("2023-04-01")), 0);
+    assert_eq!(policy.fine(due, date("2023-04-12")), 0); // 宽限期内
+    assert_eq!(policy.fine(due, date("2023-04-15")), 30);
+    assert_eq!(policy.fine(due, date("2023-06-01")), 100); // 封顶
+    assert_eq!(FinePolicy::default().fine(due, date("2023-06-01")), 0);
+}
+
+#[test]
+fn test_due_dates_and_fines() {
+    let author = Rc::new(Author {
+        name: "Steve Klabnik".into(),
+        birth_year: 1980,
+    });
+    let mut library = Library::new();
+    library
+        .add_book("Rust Programming".into(), Rc::clone(&author))
+        .unwrap();
+    library
+        .add_book_in_category("Rust Weekly".into(), author, "periodical")
+        .unwrap();
+    library.set_loan_policy(LoanPolicy::new(28).with_category("periodical", 7));
+    library.set_fine_policy(FinePolicy {
+        daily_rate: 20,
+        grace_days: 1,
+        cap: Some(500),
+    });
+    let reader = library.register_member("Alice", "alice@example.com").id;
+
+    let record = library
+        .borrow_book(reader, "Rust Programming", "2023-04-01")
+        .unwrap();
+    assert_eq!(record.due_date, date("2023-04-29"));
+    let record = library
+        .borrow_book(reader, "Rust Weekly", "2023-04-03")
+        .unwrap();
+    assert_eq!(record.due_date, date("2023-04-10"));
+
+    assert!(library.overdue_loans(date("2023-04-10")).is_empty());
+    let overdue = library.overdue_loans(date("2023-05-01"));
+    let titles: Vec<_> = overdue.iter().map(|r| r.book.title.as_str()).collect();
+    assert_eq!(titles, ["Rust Weekly", "Rust Programming"]);
+    assert_eq!(overdue[0].days_overdue(date("2023-05-01")), 21);
+
+    // 按时还书不罚款，逾期 5 天扣掉 1 天宽限期罚 4 天
+    let record = library
+        .return_book("Rust Programming", "2023-04-29")
+        .unwrap();
+    assert_eq!(record.fine, 0);
+    let record = library.return_book("Rust Weekly", "2023-04-15").unwrap();
+    assert_eq!(record.fine, 80);
+    assert_eq!(record.days_overdue(date("2023-05-01")), 5);
+    assert!(library.overdue_loans(date("2023-05-01")).is_empty());
+
+    // 应还日期超出日期范围时借书失败，副本仍然可借
+    assert_eq!(
+        LoanPolicy::default().due_date(DEFAULT_CATEGORY, NaiveDate::MAX),
+        Err(LibraryError::DateOutOfRange {
+            date: NaiveDate::MAX,
+            days: 21
+        })
+    );
+    library.set_loan_policy(LoanPolicy::new(u32::MAX));
+    assert_eq!(
+        library
+            .borrow_book(reader, "Rust Programming", "2023-05-01")
+            .unwrap_err(),
+        LibraryError::DateOutOfRange {
+            date: date("2023-05-01"),
+            days: u32::MAX
+        }
+    );
+    assert_eq!(library.available_copies("Rust Programming"), Ok(1));
+}
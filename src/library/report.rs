@@ -0,0 +1,599 @@
+// 图书馆报表：先把书籍、借阅记录和汇总整理成 Report，再交给渲染器输出。
+// 渲染器可以写到任何 io::Write，终端、文件或网络连接都可以
+use super::catalog::is_cjk;
+#[cfg(test)]
+use super::{Author, DateRange};
+use super::{BookId, Circulation, CopyStatus, HoldStatus, Isbn, Library, LoanId, MemberId};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::io;
+#[cfg(test)]
+use std::rc::Rc;
+
+// 报表的一行书籍信息
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BookRow {
+    pub id: BookId,
+    pub isbn: Option<Isbn>,
+    pub title: String,
+    pub author: String, // 主要作者
+    pub category: String,
+    pub copies: usize,    // 副本数
+    pub available: usize, // 可借的副本数
+}
+
+// 报表的一行借阅记录
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LoanRow {
+    pub id: LoanId,
+    pub book: BookId,
+    pub title: String,
+    pub barcode: String,
+    pub borrower: MemberId,
+    pub borrowed_date: NaiveDate,
+    pub due_date: NaiveDate,
+    pub return_date: Option<NaiveDate>, // 未归还为 None
+    pub renewals: usize,
+    pub fine: u64, // 以分为单位
+}
+
+// 报表的汇总
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Summary {
+    pub books: usize,
+    pub copies: usize,
+    pub available_copies: usize,
+    pub members: usize,
+    pub loans: usize,
+    pub open_loans: usize,    // 未归还的借阅
+    pub waiting_holds: usize, // 排队中的预约
+    pub fines: u64,           // 罚款总额，以分为单位
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub books: Vec<BookRow>,
+    pub loans: Vec<LoanRow>,
+    pub summary: Summary,
+    // 流通统计，用 with_circulation 加入
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub circulation: Option<Circulation>,
+}
+
+// 报表的输出格式
+pub trait ReportRenderer {
+    fn render(&self, report: &Report, out: &mut dyn io::Write) -> io::Result<()>;
+}
+
+// 列对齐的纯文本表格，中日韩文字按两个字符宽对齐
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TextRenderer;
+
+// Markdown 表格
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MarkdownRenderer;
+
+// 独立的 HTML 页面
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HtmlRenderer;
+
+// 带缩进的 JSON
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonRenderer;
+
+// 表格形式的一节：标题、表头和各行的单元格
+struct Table {
+    title: &'static str,
+    headers: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl Report {
+    // 在报表末尾加上流通统计
+    pub fn with_circulation(mut self, circulation: Circulation) -> Self {
+        self.circulation = Some(circulation);
+        self
+    }
+
+    // 书籍、借阅记录和汇总三节，有流通统计时再加四节。汇总和流通概况是两列的表格
+    fn tables(&self) -> Vec<Table> {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+        let books = self
+            .books
+            .iter()
+            .map(|book| {
+                vec![
+                    book.id.to_string(),
+                    optional(book.isbn.map(|isbn| isbn.to_string())),
+                    book.title.clone(),
+                    book.author.clone(),
+                    book.category.clone(),
+                    book.copies.to_string(),
+                    book.available.to_string(),
+                ]
+            })
+            .collect();
+        let loans = self
+            .loans
+            .iter()
+            .map(|loan| {
+                vec![
+                    loan.id.to_string(),
+                    loan.title.clone(),
+                    loan.barcode.clone(),
+                    loan.borrower.to_string(),
+                    loan.borrowed_date.to_string(),
+                    loan.due_date.to_string(),
+                    optional(loan.return_date.map(|date| date.to_string())),
+                    loan.renewals.to_string(),
+                    loan.fine.to_string(),
+                ]
+            })
+            .collect();
+        let summary = &self.summary;
+        let summary = [
+            ("Books", summary.books.to_string()),
+            ("Copies", summary.copies.to_string()),
+            ("Available copies", summary.available_copies.to_string()),
+            ("Members", summary.members.to_string()),
+            ("Loans", summary.loans.to_string()),
+            ("Open loans", summary.open_loans.to_string()),
+            ("Waiting holds", summary.waiting_holds.to_string()),
+            ("Fines", summary.fines.to_string()),
+        ]
+        .into_iter()
+        .map(|(name, value)| vec![name.to_string(), value])
+        .collect();
+        let mut tables = vec![
+            Table {
+                title: "Books",
+                headers: &[
+                    "Id",
+                    "ISBN",
+                    "Title",
+                    "Author",
+                    "Category",
+                    "Copies",
+                    "Available",
+                ],
+                rows: books,
+            },
+            Table {
+                title: "Loans",
+                headers: &[
+                    "Id", "Title", "Copy", "Borrower", "Borrowed", "Due", "Returned", "Renewals",
+                    "Fine",
+                ],
+                rows: loans,
+            },
+            Table {
+                title: "Summary",
+                headers: &["Item", "Value"],
+                rows: summary,
+            },
+        ];
+        if let Some(circulation) = &self.circulation {
+            tables.extend(circulation_tables(circulation));
+        }
+        tables
+    }
+}
+
+fn circulation_tables(circulation: &Circulation) -> [Table; 4] {
+    let range = circulation.range;
+    let optional = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+    let duration = circulation.duration;
+    let overview = [
+        ("Period", format!("{} to {}", range.from, range.to)),
+        ("Loans", circulation.loans.to_string()),
+        (
+            "Mean loan days",
+            optional(duration.map(|d| format!("{:.1}", d.mean_days))),
+        ),
+        (
+            "Median loan days",
+            optional(duration.map(|d| format!("{:.1}", d.median_days))),
+        ),
+        (
+            "On-time returns",
+            optional(
+                circulation
+                    .on_time_rate
+                    .map(|rate| format!("{:.1}%", rate * 100.0)),
+            ),
+        ),
+        ("Currently out", circulation.out.loans.to_string()),
+        ("Overdue", circulation.out.overdue.to_string()),
+    ]
+    .into_iter()
+    .map(|(name, value)| vec![name.to_string(), value])
+    .collect();
+    [
+        Table {
+            title: "Circulation",
+            headers: &["Item", "Value"],
+            rows: overview,
+        },
+        Table {
+            title: "Most Borrowed Titles",
+            headers: &["Id", "Title", "Loans"],
+            rows: circulation
+                .top_titles
+                .iter()
+                .map(|c| vec![c.book.to_string(), c.title.clone(), c.loans.to_string()])
+                .collect(),
+        },
+        Table {
+            title: "Most Borrowed Authors",
+            headers: &["Id", "Author", "Loans"],
+            rows: circulation
+                .top_authors
+                .iter()
+                .map(|c| vec![c.author.to_string(), c.name.clone(), c.loans.to_string()])
+                .collect(),
+        },
+        Table {
+            title: "Loans per Month",
+            headers: &["Month", "Loans"],
+            rows: circulation
+                .per_month
+                .iter()
+                .map(|m| vec![format!("{}-{:02}", m.year, m.month), m.loans.to_string()])
+                .collect(),
+        },
+    ]
+}
+
+// 终端中的显示宽度
+fn width(text: &str) -> usize {
+    text.chars().map(|ch| if is_cjk(ch) { 2 } else { 1 }).sum()
+}
+
+impl ReportRenderer for TextRenderer {
+    fn render(&self, report: &Report, out: &mut dyn io::Write) -> io::Result<()> {
+        for (i, table) in report.tables().iter().enumerate() {
+            if i > 0 {
+                writeln!(out)?;
+            }
+            writeln!(out, "{}", table.title)?;
+            let rows: Vec<Vec<String>> = table
+                .rows
+                .iter()
+                .map(|row| row.iter().map(|cell| text_cell(cell)).collect())
+                .collect();
+            let mut widths: Vec<usize> = table.headers.iter().map(|h| width(h)).collect();
+            for row in &rows {
+                for (w, cell) in widths.iter_mut().zip(row) {
+                    *w = (*w).max(width(cell));
+                }
+            }
+            let line = |cells: &mut dyn Iterator<Item = &str>| {
+                let padded: Vec<String> = cells
+                    .zip(&widths)
+                    .map(|(cell, &w)| format!("{}{}", cell, " ".repeat(w - width(cell))))
+                    .collect();
+                padded.join("  ").trim_end().to_string()
+            };
+            writeln!(out, "{}", line(&mut table.headers.iter().copied()))?;
+            let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w)).collect();
+            writeln!(out, "{}", rule.join("  "))?;
+            for row in &rows {
+                writeln!(out, "{}", line(&mut row.iter().map(String::as_str)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// 文本表格按列对齐，单元格里的换行换成空格
+fn text_cell(text: &str) -> String {
+    text.replace("\r\n", " ").replace(['\r', '\n'], " ")
+}
+
+// Markdown 表格的单元格只能占一行：反斜杠和竖线要转义，换行换成 <br>；
+// Markdown 会原样输出其中的 HTML，因此 &、<、> 也要转义
+fn markdown_cell(text: &str) -> String {
+    let mut cell = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => cell.push_str("\\\\"),
+            '|' => cell.push_str("\\|"),
+            '&' => cell.push_str("&amp;"),
+            '<' => cell.push_str("&lt;"),
+            '>' => cell.push_str("&gt;"),
+            '\r' | '\n' => {
+                // \r\n 算作一次换行
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                cell.push_str("<br>");
+            }
+            _ => cell.push(c),
+        }
+    }
+    cell
+}
+
+impl ReportRenderer for MarkdownRenderer {
+    fn render(&self, report: &Report, out: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(out, "# Library Report")?;
+        for table in report.tables() {
+            writeln!(out)?;
+            writeln!(out, "## {}", table.title)?;
+            writeln!(out)?;
+            writeln!(out, "| {} |", table.headers.join(" | "))?;
+            let rule = vec!["---"; table.headers.len()];
+            writeln!(out, "| {} |", rule.join(" | "))?;
+            for row in &table.rows {
+                let cells: Vec<String> = row.iter().map(|cell| markdown_cell(cell)).collect();
+                writeln!(out, "| {} |", cells.join(" | "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+impl ReportRenderer for HtmlRenderer {
+    fn render(&self, report: &Report, out: &mut dyn io::Write) -> io::Result<()> {
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(out, "<html>")?;
+        writeln!(out, "<head>")?;
+        writeln!(out, "<meta charset=\"utf-8\">")?;
+        writeln!(out, "<title>Library Report</title>")?;
+        writeln!(out, "</head>")?;
+        writeln!(out, "<body>")?;
+        writeln!(out, "<h1>Library Report</h1>")?;
+        for table in report.tables() {
+            writeln!(out, "<h2>{}</h2>", table.title)?;
+            writeln!(out, "<table>")?;
+            write!(out, "<tr>")?;
+            for header in table.headers {
+                write!(out, "<th>{}</th>", header)?;
+            }
+            writeln!(out, "</tr>")?;
+            for row in &table.rows {
+                write!(out, "<tr>")?;
+                for cell in row {
+                    write!(out, "<td>{}</td>", html_escape(cell))?;
+                }
+                writeln!(out, "</tr>")?;
+            }
+            writeln!(out, "</table>")?;
+        }
+        writeln!(out, "</body>")?;
+        writeln!(out, "</html>")
+    }
+}
+
+impl ReportRenderer for JsonRenderer {
+    fn render(&self, report: &Report, out: &mut dyn io::Write) -> io::Result<()> {
+        serde_json::to_writer_pretty(&mut *out, report)?;
+        writeln!(out)
+    }
+}
+
+impl Library {
+    // 当前书籍、借阅记录和汇总的报表
+    pub fn report(&self) -> Report {
+        let books = self
+            .books
+            .iter()
+            .map(|book| {
+                let copies = self.copies.iter().filter(|copy| copy.book.id == book.id);
+                BookRow {
+                    id: book.id,
+                    isbn: book.isbn,
+                    title: book.title.clone(),
+                    author: book.author.name.clone(),
+                    category: book.category.clone(),
+                    copies: copies.clone().count(),
+                    available: copies
+                        .filter(|copy| copy.status == CopyStatus::Available)
+                        .count(),
+                }
+            })
+            .collect();
+        let loans: Vec<LoanRow> = self
+            .borrow_records
+            .iter()
+            .map(|record| LoanRow {
+                id: record.id,
+                book: record.book.id,
+                title: record.book.title.clone(),
+                barcode: record.barcode.clone(),
+                borrower: record.borrower,
+                borrowed_date: record.borrowed_date,
+                due_date: record.due_date,
+                return_date: record.return_date,
+                renewals: record.renewals.len(),
+                fine: record.fine,
+            })
+            .collect();
+        let summary = Summary {
+            books: self.books.len(),
+            copies: self.copies.len(),
+            available_copies: self
+                .copies
+                .iter()
+                .filter(|copy| copy.status == CopyStatus::Available)
+                .count(),
+            members: self.members.len(),
+            loans: loans.len(),
+            open_loans: loans
+                .iter()
+                .filter(|loan| loan.return_date.is_none())
+                .count(),
+            waiting_holds: self
+                .holds
+                .iter()
+                .filter(|hold| hold.status == HoldStatus::Waiting)
+                .count(),
+            fines: loans.iter().map(|loan| loan.fine).sum(),
+        };
+        Report {
+            books,
+            loans,
+            summary,
+            circulation: None,
+        }
+    }
+
+    // 用 renderer 把报表写到 out
+    pub fn write_report(
+        &self,
+        renderer: &dyn ReportRenderer,
+        out: &mut dyn io::Write,
+    ) -> io::Result<()> {
+        renderer.render(&self.report(), out)
+    }
+}
+
+#[cfg(test)]
+fn sample_library() -> Library {
+    let author = Rc::new(Author {
+        name: "张汉东".into(),
+        birth_year: 1985,
+    });
+    let mut library = Library::new();
+    library
+        .add_book("Rust 编程之道".into(), Rc::clone(&author))
+        .unwrap();
+    library
+        .add_book("<Rust> & Co | Tips".into(), author)
+        .unwrap();
+    library
+        .add_copy("Rust 编程之道", "RB-2", "2023-04-01")
+        .unwrap();
+    let alice = library.register_member("Alice", "alice@example.com").id;
+    library
+        .borrow_book(alice, "Rust 编程之道", "2023-04-01")
+        .unwrap();
+    library.return_book("Rust 编程之道", "2023-04-30").unwrap();
+    library
+        .borrow_book(alice, "<Rust> & Co | Tips", "2023-05-02")
+        .unwrap();
+    library
+}
+
+#[cfg(test)]
+fn render(renderer: &dyn ReportRenderer, library: &Library) -> String {
+    let mut out = Vec::new();
+    library.write_report(renderer, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_report_model() {
+    let library = sample_library();
+    let report = library.report();
+    assert_eq!((report.books[0].copies, report.books[0].available), (2, 2));
+    assert_eq!((report.books[1].copies, report.books[1].available), (1, 0));
+    assert_eq!(report.loans[1].return_date, None);
+    assert_eq!(
+        report.summary,
+        Summary {
+            books: 2,
+            copies: 3,
+            available_copies: 2,
+            members: 1,
+            loans: 2,
+            open_loans: 1,
+            waiting_holds: 0,
+            fines: 0,
+        }
+    );
+
+    let json: serde_json::Value = serde_json::from_str(&render(&JsonRenderer, &library)).unwrap();
+    assert_eq!(json["books"][0]["id"], 1);
+    assert_eq!(json["loans"][1]["return_date"], serde_json::Value::Null);
+    assert_eq!(json["summary"]["open_loans"], 1);
+}
+
+#[test]
+fn test_render_text() {
+    let text = render(&TextRenderer, &sample_library());
+    let books: Vec<&str> = text.lines().take(5).collect();
+    // 中文按两个字符宽对齐
+    assert_eq!(
+        books,
+        [
+            "Books",
+            "Id     ISBN  Title               Author  Category  Copies  Available",
+            "-----  ----  ------------------  ------  --------  ------  ---------",
+            "B0001  -     Rust 编程之道       张汉东  general   2       2",
+            "B0002  -     <Rust> & Co | Tips  张汉东  general   1       0",
+        ]
+    );
+    assert!(text.contains("\nOpen loans        1\n"));
+    // 换行会打乱对齐，换成空格
+    assert_eq!(text_cell("Rust\r\nin\nAction\r"), "Rust in Action ");
+}
+
+#[test]
+fn test_render_markdown_and_html() {
+    let library = sample_library();
+    let markdown = render(&MarkdownRenderer, &library);
+    assert!(markdown.starts_with("# Library Report\n\n## Books\n\n| Id | ISBN |"));
+    assert!(markdown
+        .contains("| B0002 | - | &lt;Rust&gt; &amp; Co \\| Tips | 张汉东 | general | 1 | 0 |\n"));
+    assert!(markdown.contains("| L0002 | &lt;Rust&gt; &amp; Co \\| Tips | C000002 | M0001 | 2023-05-02 | 2023-05-23 | - | 0 | 0 |\n"));
+    assert_eq!(
+        markdown_cell("a\\|b\r\nc\nd\re|"),
+        "a\\\\\\|b<br>c<br>d<br>e\\|"
+    );
+    assert_eq!(
+        markdown_cell("<script>alert(1)</script>"),
+        "&lt;script&gt;alert(1)&lt;/script&gt;"
+    );
+
+    let html = render(&HtmlRenderer, &library);
+    assert!(html.starts_with("<!DOCTYPE html>\n<html>\n"));
+    assert!(html.contains("<td>&lt;Rust&gt; &amp; Co | Tips</td>"));
+    assert!(html.trim_end().ends_with("</html>"));
+}
+
+#[test]
+fn test_render_circulation() {
+    let library = sample_library();
+    let range = DateRange::new("2023-04-01", "2023-05-31").unwrap();
+    let report = library
+        .report()
+        .with_circulation(library.circulation(range, 5));
+    let output = |renderer: &dyn ReportRenderer| {
+        let mut out = Vec::new();
+        renderer.render(&report, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    let text = output(&TextRenderer);
+    assert!(text.contains("\nPeriod            2023-04-01 to 2023-05-31\n"));
+    assert!(text.contains("\nOn-time returns   0.0%\n"));
+    assert!(text.contains("\nMean loan days    29.0\n"));
+    let markdown = output(&MarkdownRenderer);
+    assert!(markdown.contains("## Loans per Month\n\n| Month | Loans |\n| --- | --- |\n| 2023-04 | 1 |\n| 2023-05 | 1 |\n"));
+    assert!(markdown.contains("| A0001 | 张汉东 | 2 |\n"));
+    assert!(output(&HtmlRenderer).contains("<h2>Most Borrowed Titles</h2>"));
+
+    let json: serde_json::Value = serde_json::from_str(&output(&JsonRenderer)).unwrap();
+    assert_eq!(json["circulation"]["loans"], 2);
+    assert_eq!(json["circulation"]["out"]["loans"], 1);
+    // 没有流通统计时 JSON 里也没有这一项
+    let json: serde_json::Value = serde_json::from_str(&render(&JsonRenderer, &library)).unwrap();
+    assert!(json.get("circulation").is_none());
+}
@@ -0,0 +1,710 @@
+// 表格导入导出：从 CSV 导入书籍，把书籍和借阅记录导出为 CSV。
+// 导入时按表头找列，列名可以配置；有问题的行跳过并记下行号和原因，其余行照常导入。
+// 作者和 add_book 一样登记到作者登记表，日期和 borrow_book 一样使用 YYYY-MM-DD 格式
+#[cfg(test)]
+use super::BookBuilder;
+use super::{
+    Author, Book, BookMetadata, Contributor, Isbn, IsbnError, Library, LibraryError, Role,
+};
+use std::io;
+use std::rc::Rc;
+use std::{error, fmt};
+
+// 书籍表格各字段对应的列名。书名必须有；作者和作者出生年份也必须有，
+// 除非有贡献者一列，其余列可以没有。分号分隔的列中，值本身的分号写作 \;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookColumns {
+    pub title: String,
+    pub author: String,
+    pub birth_year: String,
+    // 全部作者、编者和译者，写作“姓名 (出生年份, 角色)”，多人用分号分隔；
+    // 有内容时代替作者和出生年份两列
+    pub contributors: String,
+    pub category: String,
+    pub isbn: String,
+    pub publisher: String,
+    pub year: String,
+    pub edition: String,
+    pub language: String,
+    pub pages: String,
+    pub genres: String, // 多个体裁用分号分隔
+    pub tags: String,   // 多个标签用分号分隔
+}
+
+impl Default for BookColumns {
+    // 默认列名与 export_books_csv 写出的表头一致
+    fn default() -> Self {
+        BookColumns {
+            title: "title".into(),
+            author: "author".into(),
+            birth_year: "birth_year".into(),
+            contributors: "contributors".into(),
+            category: "category".into(),
+            isbn: "isbn".into(),
+            publisher: "publisher".into(),
+            year: "year".into(),
+            edition: "edition".into(),
+            language: "language".into(),
+            pages: "pages".into(),
+            genres: "genres".into(),
+            tags: "tags".into(),
+        }
+    }
+}
+
+// 整个表格无法处理的错误
+#[derive(Debug)]
+pub enum CsvError {
+    // 读写或 CSV 格式错误
+    Csv(csv::Error),
+    // 表头里没有必需的列
+    MissingColumn { column: String },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Csv(e) => write!(f, "csv error: {}", e),
+            CsvError::MissingColumn { column } => write!(f, "missing column '{}'", column),
+        }
+    }
+}
+
+impl error::Error for CsvError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CsvError::Csv(e) => Some(e),
+            CsvError::MissingColumn { .. } => None,
+        }
+    }
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(e: csv::Error) -> Self {
+        CsvError::Csv(e)
+    }
+}
+
+// 某一行没有导入的原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowErrorKind {
+    // 这一行无法解析为 CSV 记录
+    Malformed { reason: String },
+    // 必填的列为空
+    EmptyField { column: String },
+    // 应为整数的列不是整数
+    InvalidNumber { column: String, value: String },
+    // 贡献者不是“姓名 (出生年份, 角色)”的格式
+    InvalidContributor { value: String },
+    InvalidIsbn(IsbnError),
+    // 图书馆拒绝了这本书，如 ISBN 重复
+    Rejected(LibraryError),
+}
+
+impl fmt::Display for RowErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RowErrorKind::Malformed { reason } => f.write_str(reason),
+            RowErrorKind::EmptyField { column } => write!(f, "column '{}' is empty", column),
+            RowErrorKind::InvalidNumber { column, value } => {
+                write!(f, "column '{}' is not a number: '{}'", column, value)
+            }
+            RowErrorKind::InvalidContributor { value } => {
+                write!(f, "invalid contributor '{}'", value)
+            }
+            RowErrorKind::InvalidIsbn(e) => e.fmt(f),
+            RowErrorKind::Rejected(e) => e.fmt(f),
+        }
+    }
+}
+
+// 没有导入的一行，line 是表格中的行号（表头是第 1 行）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    pub line: u64,
+    pub kind: RowErrorKind,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+// 导入的结果：导入的书按行的顺序排列，没有导入的行按行号排列
+#[derive(Debug, Default)]
+pub struct CsvImport {
+    pub imported: Vec<Rc<Book>>,
+    pub errors: Vec<RowError>,
+}
+
+// 表头中各字段所在的位置
+struct Positions {
+    title: usize,
+    author: Option<usize>,
+    birth_year: Option<usize>,
+    contributors: Option<usize>,
+    category: Option<usize>,
+    isbn: Option<usize>,
+    publisher: Option<usize>,
+    year: Option<usize>,
+    edition: Option<usize>,
+    language: Option<usize>,
+    pages: Option<usize>,
+    genres: Option<usize>,
+    tags: Option<usize>,
+}
+
+impl Positions {
+    fn new(headers: &csv::StringRecord, columns: &BookColumns) -> Result<Positions, CsvError> {
+        let find = |name: &str| headers.iter().position(|h| h.trim() == name);
+        let require = |name: &str| {
+            find(name).ok_or_else(|| CsvError::MissingColumn {
+                column: name.to_string(),
+            })
+        };
+        let contributors = find(&columns.contributors);
+        // 有贡献者一列时作者和出生年份两列可以没有
+        let author_column = |name: &str| match contributors {
+            Some(_) => Ok(find(name)),
+            None => require(name).map(Some),
+        };
+        Ok(Positions {
+            title: require(&columns.title)?,
+            author: author_column(&columns.author)?,
+            birth_year: author_column(&columns.birth_year)?,
+            contributors,
+            category: find(&columns.category),
+            isbn: find(&columns.isbn),
+            publisher: find(&columns.publisher),
+            year: find(&columns.year),
+            edition: find(&columns.edition),
+            language: find(&columns.language),
+            pages: find(&columns.pages),
+            genres: find(&columns.genres),
+            tags: find(&columns.tags),
+        })
+    }
+}
+
+// 一行中的字段，去掉首尾空格，空的字段视为没有
+struct Row<'r> {
+    record: &'r csv::StringRecord,
+    columns: &'r BookColumns,
+}
+
+impl Row<'_> {
+    fn get(&self, position: Option<usize>) -> Option<&str> {
+        let field = self.record.get(position?)?.trim();
+        (!field.is_empty()).then_some(field)
+    }
+
+    fn required(&self, position: Option<usize>, column: &str) -> Result<&str, RowErrorKind> {
+        self.get(position).ok_or_else(|| RowErrorKind::EmptyField {
+            column: column.to_string(),
+        })
+    }
+
+    fn number<T: std::str::FromStr>(
+        &self,
+        position: Option<usize>,
+        column: &str,
+    ) -> Result<Option<T>, RowErrorKind> {
+        self.get(position)
+            .map(|value| {
+                value.parse().map_err(|_| RowErrorKind::InvalidNumber {
+                    column: column.to_string(),
+                    value: value.to_string(),
+                })
+            })
+            .transpose()
+    }
+
+    // 分号分隔的列表
+    fn list(&self, position: Option<usize>) -> Vec<String> {
+        self.get(position).map(split_list).unwrap_or_default()
+    }
+
+    // 这本书的贡献者：有贡献者一列时按它解析，否则由作者和出生年份两列组成唯一的著者
+    fn contributors(&self, positions: &Positions) -> Result<Vec<(Author, Role)>, RowErrorKind> {
+        let columns = self.columns;
+        let listed = self.list(positions.contributors);
+        if !listed.is_empty() {
+            return listed
+                .iter()
+                .map(|value| parse_contributor(value))
+                .collect();
+        }
+        let name = self.required(positions.author, &columns.author)?;
+        let birth_year = self.required(positions.birth_year, &columns.birth_year)?;
+        let birth_year = birth_year
+            .parse()
+            .map_err(|_| RowErrorKind::InvalidNumber {
+                column: columns.birth_year.clone(),
+                value: birth_year.to_string(),
+            })?;
+        let author = Author {
+            name: name.to_string(),
+            birth_year,
+        };
+        Ok(vec![(author, Role::Author)])
+    }
+}
+
+// 拆开分号分隔的列表，与 join_list 互逆：反斜杠后的字符按原样保留，
+// 因此项内的分号写作 \;，反斜杠写作 \\
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut item = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => item.extend(chars.next()),
+            ';' => items.push(std::mem::take(&mut item)),
+            _ => item.push(c),
+        }
+    }
+    items.push(item);
+    items
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn join_list<T: AsRef<str>>(items: impl IntoIterator<Item = T>, separator: &str) -> String {
+    items
+        .into_iter()
+        .map(|item| item.as_ref().replace('\\', "\\\\").replace(';', "\\;"))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+// 贡献者一列中的一项，与 format_contributor 互逆
+fn parse_contributor(value: &str) -> Result<(Author, Role), RowErrorKind> {
+    let invalid = || RowErrorKind::InvalidContributor {
+        value: value.to_string(),
+    };
+    let (name, rest) = value.rsplit_once('(').ok_or_else(invalid)?;
+    let (birth_year, role) = rest
+        .strip_suffix(')')
+        .and_then(|rest| rest.split_once(','))
+        .ok_or_else(invalid)?;
+    let role = match role.trim() {
+        "author" => Role::Author,
+        "editor" => Role::Editor,
+        "translator" => Role::Translator,
+        _ => return Err(invalid()),
+    };
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(invalid());
+    }
+    let author = Author {
+        name: name.to_string(),
+        birth_year: birth_year.trim().parse().map_err(|_| invalid())?,
+    };
+    Ok((author, role))
+}
+
+fn format_contributor(contributor: &Contributor) -> String {
+    let author = &contributor.author;
+    format!(
+        "{} ({}, {})",
+        author.name, author.birth_year, contributor.role
+    )
+}
+
+impl Library {
+    // 从 CSV 导入书籍，每行一种书，各入藏一本自动编号的副本。
+    // 表头缺少必需的列时什么也不导入；有问题的行跳过，记在结果的 errors 里
+    pub fn import_books_csv(
+        &mut self,
+        reader: impl io::Read,
+        columns: &BookColumns,
+    ) -> Result<CsvImport, CsvError> {
+        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let positions = Positions::new(reader.headers()?, columns)?;
+        let mut result = CsvImport::default();
+        let mut record = csv::StringRecord::new();
+        loop {
+            let line = reader.position().line();
+            match reader.read_record(&mut record) {
+                Ok(false) => break,
+                Ok(true) => {}
+                // 读写错误无法继续，格式错误只影响这一行
+                Err(e) if e.is_io_error() => return Err(e.into()),
+                Err(e) => {
+                    result.errors.push(RowError {
+                        line,
+                        kind: RowErrorKind::Malformed {
+                            reason: e.to_string(),
+                        },
+                    });
+                    continue;
+                }
+            }
+            let line = record.position().map_or(line, |p| p.line());
+            let row = Row {
+                record: &record,
+                columns,
+            };
+            match self.import_row(&row, &positions) {
+                Ok(book) => result.imported.push(book),
+                Err(kind) => result.errors.push(RowError { line, kind }),
+            }
+        }
+        Ok(result)
+    }
+
+    fn import_row(&mut self, row: &Row, positions: &Positions) -> Result<Rc<Book>, RowErrorKind> {
+        let columns = row.columns;
+        let title = row.required(Some(positions.title), &columns.title)?;
+        let contributors = row.contributors(positions)?;
+        let isbn = row
+            .get(positions.isbn)
+            .map(Isbn::parse)
+            .transpose()
+            .map_err(RowErrorKind::InvalidIsbn)?;
+        let category = row
+            .get(positions.category)
+            .unwrap_or(super::DEFAULT_CATEGORY);
+        let metadata = BookMetadata {
+            publisher: row.get(positions.publisher).map(String::from),
+            year: row.number(positions.year, &columns.year)?,
+            edition: row.number(positions.edition, &columns.edition)?,
+            language: row.get(positions.language).map(String::from),
+            pages: row.number(positions.pages, &columns.pages)?,
+            genres: row.list(positions.genres),
+            tags: row.list(positions.tags),
+        };
+        // ISBN 重复时不登记作者，免得留下没有书的作者
+        if let Some(isbn) = isbn {
+            if self.by_isbn.contains_key(&isbn) {
+                return Err(RowErrorKind::Rejected(LibraryError::DuplicateIsbn { isbn }));
+            }
+        }
+        let contributors = contributors
+            .into_iter()
+            .map(|(author, role)| Contributor {
+                author: self.authors.intern(Rc::new(author)).1,
+                role,
+            })
+            .collect();
+        self.insert_book(
+            title.to_string(),
+            contributors,
+            category.to_string(),
+            isbn,
+            metadata,
+        )
+        .map_err(RowErrorKind::Rejected)
+    }
+
+    // 按入藏顺序把书籍写成 CSV，表头是默认列名，另有一列书籍编号。
+    // 作者和出生年份两列写主要作者，贡献者一列写出全部贡献者及其角色，
+    // 写出的表格可以用默认列名重新导入
+    pub fn export_books_csv(&self, writer: impl io::Write) -> Result<(), CsvError> {
+        let columns = BookColumns::default();
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record([
+            "id",
+            &columns.title,
+            &columns.author,
+            &columns.birth_year,
+            &columns.contributors,
+            &columns.category,
+            &columns.isbn,
+            &columns.publisher,
+            &columns.year,
+            &columns.edition,
+            &columns.language,
+            &columns.pages,
+            &columns.genres,
+            &columns.tags,
+        ])?;
+        let text = |value: Option<String>| value.unwrap_or_default();
+        for book in &self.books {
+            let metadata = &book.metadata;
+            writer.write_record([
+                book.id.to_string(),
+                book.title.clone(),
+                book.author.name.clone(),
+                book.author.birth_year.to_string(),
+                join_list(book.contributors.iter().map(format_contributor), "; "),
+                book.category.clone(),
+                text(book.isbn.map(|isbn| isbn.to_string())),
+                text(metadata.publisher.clone()),
+                text(metadata.year.map(|year| year.to_string())),
+                text(metadata.edition.map(|edition| edition.to_string())),
+                text(metadata.language.clone()),
+                text(metadata.pages.map(|pages| pages.to_string())),
+                join_list(&metadata.genres, ";"),
+                join_list(&metadata.tags, ";"),
+            ])?;
+        }
+        writer.flush().map_err(csv::Error::from)?;
+        Ok(())
+    }
+
+    // 按借书顺序把借阅记录写成 CSV，日期为 YYYY-MM-DD，未归还的还书日期为空，罚款以分为单位
+    pub fn export_loans_csv(&self, writer: impl io::Write) -> Result<(), CsvError> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record([
+            "id",
+            "book",
+            "title",
+            "barcode",
+            "borrower",
+            "borrowed_date",
+            "due_date",
+            "return_date",
+            "renewals",
+            "fine",
+        ])?;
+        for record in &self.borrow_records {
+            writer.write_record([
+                record.id.to_string(),
+                record.book.id.to_string(),
+                record.book.title.clone(),
+                record.barcode.clone(),
+                record.borrower.to_string(),
+                record.borrowed_date.to_string(),
+                record.due_date.to_string(),
+                record
+                    .return_date
+                    .map(|date| date.to_string())
+                    .unwrap_or_default(),
+                record.renewals.len().to_string(),
+                record.fine.to_string(),
+            ])?;
+        }
+        writer.flush().map_err(csv::Error::from)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_import_books_csv() {
+    let csv = "\
+书名,作者,出生年份,ISBN,年份,体裁
+Rust 编程之道,张汉东,1985,,2019,programming; rust
+The Rust Book,Steve Klabnik,1980,978-1-7185-0044-0,2023,
+Rust in Action,Tim McNamara,,,2021,
+Copy,Steve Klabnik,1980,1718500440,,
+Bad ISBN,Steve Klabnik,1980,978-1-7185-0044-1,,
+Rust Atomics,Mara Bos,1993,,twenty,
+";
+    let columns = BookColumns {
+        title: "书名".into(),
+        author: "作者".into(),
+        birth_year: "出生年份".into(),
+        isbn: "ISBN".into(),
+        year: "年份".into(),
+        genres: "体裁".into(),
+        ..BookColumns::default()
+    };
+    let mut library = Library::new();
+    let klabnik = library.register_author("steve  klabnik", 1980);
+    let result = library.import_books_csv(csv.as_bytes(), &columns).unwrap();
+
+    let titles: Vec<_> = result.imported.iter().map(|b| b.title.as_str()).collect();
+    assert_eq!(titles, ["Rust 编程之道", "The Rust Book"]);
+    let book = &result.imported[0];
+    assert_eq!(book.metadata.year, Some(2019));
+    assert_eq!(book.metadata.genres, ["programming", "rust"]);
+    assert_eq!(book.category, super::DEFAULT_CATEGORY);
+    assert_eq!(book.contributors.len(), 1);
+    // 作者和 add_book 一样合并到已登记的作者
+    assert!(Rc::ptr_eq(
+        &result.imported[1].author,
+        library.author(klabnik).unwrap()
+    ));
+    assert_eq!(library.authors().len(), 2);
+    assert_eq!(library.copies.len(), 2);
+
+    let isbn = Isbn::parse("1718500440").unwrap();
+    let lines: Vec<_> = result.errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, [4, 5, 6, 7]);
+    assert_eq!(
+        result.errors[0].kind,
+        RowErrorKind::EmptyField {
+            column: "出生年份".into()
+        }
+    );
+    assert_eq!(
+        result.errors[1].kind,
+        RowErrorKind::Rejected(LibraryError::DuplicateIsbn { isbn })
+    );
+    assert_eq!(
+        result.errors[2].to_string(),
+        "line 6: ISBN checksum does not match"
+    );
+    assert_eq!(
+        result.errors[3].kind,
+        RowErrorKind::InvalidNumber {
+            column: "年份".into(),
+            value: "twenty".into()
+        }
+    );
+
+    let err = library
+        .import_books_csv("name,author\n".as_bytes(), &BookColumns::default())
+        .unwrap_err();
+    assert_eq!(err.to_string(), "missing column 'title'");
+}
+
+#[test]
+fn test_export_csv() {
+    let mut library = Library::new();
+    let zhang = library.register_author("张汉东", 1985);
+    BookBuilder::new("Rust 编程之道")
+        .with_author(zhang)
+        .with_isbn(Isbn::parse("978-7-121-36203-3").unwrap())
+        .with_year(2019)
+        .with_genre("programming")
+        .with_genre("rust")
+        .build(&mut library)
+        .unwrap();
+    let author = Rc::clone(library.author(zhang).unwrap());
+    library
+        .add_book_in_category("Rust, \"the\" Book".into(), author, "reference")
+        .unwrap();
+    let reader = library.register_member("Alice", "alice@example.com").id;
+    library
+        .borrow_book(reader, "Rust 编程之道", "2023-04-01")
+        .unwrap();
+    library.return_book("Rust 编程之道", "2023-04-30").unwrap();
+    library
+        .borrow_book(reader, "Rust 编程之道", "2023-05-02")
+        .unwrap();
+
+    let mut books = Vec::new();
+    library.export_books_csv(&mut books).unwrap();
+    assert_eq!(
+        String::from_utf8(books.clone()).unwrap(),
+        "\
+id,title,author,birth_year,contributors,category,isbn,publisher,year,edition,language,pages,genres,tags
+B0001,Rust 编程之道,张汉东,1985,\"张汉东 (1985, author)\",general,9787121362033,,2019,,,,programming;rust,
+B0002,\"Rust, \"\"the\"\" Book\",张汉东,1985,\"张汉东 (1985, author)\",reference,,,,,,,,
+"
+    );
+    // 导出的表格可以原样导入另一个图书馆
+    let mut copy = Library::new();
+    let result = copy
+        .import_books_csv(books.as_slice(), &BookColumns::default())
+        .unwrap();
+    assert!(result.errors.is_empty());
+    assert_eq!(copy.books[1].title, "Rust, \"the\" Book");
+    assert_eq!(copy.books[0].metadata, library.books[0].metadata);
+    assert_eq!(copy.authors().len(), 1);
+
+    let mut loans = Vec::new();
+    library.export_loans_csv(&mut loans).unwrap();
+    assert_eq!(
+        String::from_utf8(loans).unwrap(),
+        "\
+id,book,title,barcode,borrower,borrowed_date,due_date,return_date,renewals,fine
+L0001,B0001,Rust 编程之道,C000001,M0001,2023-04-01,2023-04-22,2023-04-30,0,0
+L0002,B0001,Rust 编程之道,C000001,M0001,2023-05-02,2023-05-23,,0,0
+"
+    );
+}
+
+#[test]
+fn test_export_list_escapes() {
+    // 值本身带分号或反斜杠时导出后还能原样导入
+    let mut library = Library::new();
+    let author = library.register_author("Doe; Jane", 1970);
+    BookBuilder::new("Odd Values")
+        .with_contributor(author, Role::Editor)
+        .with_genre("science; fiction")
+        .with_genre("C\\C++")
+        .with_tag(";")
+        .build(&mut library)
+        .unwrap();
+    let mut books = Vec::new();
+    library.export_books_csv(&mut books).unwrap();
+    let books = String::from_utf8(books).unwrap();
+    assert!(books.contains(r"Doe\; Jane (1970, editor)"));
+    assert!(books.contains(r"science\; fiction;C\\C++,\;"));
+
+    let mut copy = Library::new();
+    let result = copy
+        .import_books_csv(books.as_bytes(), &BookColumns::default())
+        .unwrap();
+    assert!(result.errors.is_empty());
+    let book = &copy.books[0];
+    assert_eq!(book.contributors[0].author.name, "Doe; Jane");
+    assert_eq!(book.contributors[0].role, Role::Editor);
+    assert_eq!(book.metadata, library.books[0].metadata);
+}
+
+#[test]
+fn test_export_contributors() {
+    // 全部贡献者连同角色一起导出，导入时按原来的顺序和角色登记
+    let mut library = Library::new();
+    let klabnik = library.register_author("Steve Klabnik", 1980);
+    let nichols = library.register_author("Carol Nichols", 1983);
+    let translator = library.register_author("李明", 1990);
+    library
+        .add_book_with_contributors(
+            "Rust 程序设计语言".into(),
+            &[
+                (klabnik, Role::Author),
+                (nichols, Role::Author),
+                (translator, Role::Translator),
+            ],
+        )
+        .unwrap();
+    let mut books = Vec::new();
+    library.export_books_csv(&mut books).unwrap();
+    let books = String::from_utf8(books).unwrap();
+    assert!(books.contains(
+        "\"Steve Klabnik (1980, author); Carol Nichols (1983, author); 李明 (1990, translator)\""
+    ));
+    let mut copy = Library::new();
+    let result = copy
+        .import_books_csv(books.as_bytes(), &BookColumns::default())
+        .unwrap();
+    assert!(result.errors.is_empty());
+    let contributors: Vec<_> = copy.books[0]
+        .contributors
+        .iter()
+        .map(|c| (c.author.name.as_str(), c.author.birth_year, c.role))
+        .collect();
+    assert_eq!(
+        contributors,
+        [
+            ("Steve Klabnik", 1980, Role::Author),
+            ("Carol Nichols", 1983, Role::Author),
+            ("李明", 1990, Role::Translator),
+        ]
+    );
+    assert_eq!(copy.books[0].author.name, "Steve Klabnik");
+    assert_eq!(copy.authors().len(), 3);
+
+    // 有贡献者一列时不需要作者和出生年份两列
+    let csv = "\
+title,contributors
+Rust Atomics,Mara Bos (1993; editor)
+Rust Atomics,Mara Bos (1993)
+Rust Atomics,\"Mara Bos (1993, editor)\"
+";
+    let result = copy
+        .import_books_csv(csv.as_bytes(), &BookColumns::default())
+        .unwrap();
+    assert_eq!(result.imported.len(), 1);
+    assert_eq!(result.imported[0].contributors[0].role, Role::Editor);
+    assert_eq!(
+        result.errors[0].to_string(),
+        "line 2: invalid contributor 'Mara Bos (1993'"
+    );
+    assert_eq!(
+        result.errors[1].kind,
+        RowErrorKind::InvalidContributor {
+            value: "Mara Bos (1993)".into()
+        }
+    );
+}
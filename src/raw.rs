@@ -0,0 +1,197 @@
+use alloc::boxed::Box;
+
+// 裸指针所有权的唯一出入口：所有通过 AtomicPtr 共享的堆上的值都由这里分配、读取和释放。
+// debug 构建下会登记每个存活的分配，发现重复释放、读取已释放或未知的指针时，
+// 带上出错的调用位置直接 panic；release 构建（以及没有 std 的构建）下这些检查全部编译掉。
+// 检查是尽力而为的：读者通过检查之后、真正读取之前被释放的情况仍需要 loom 来发现
+
+// 通过 AtomicPtr 共享的堆上的值。
+// loom 下包一层 loom 的 UnsafeCell：读取记为一次读访问，释放前记为一次写访问，
+// 如果释放时还有读者与之没有先后关系（即释放后读），loom 会报告这个交错；
+// 另外用 freed 标记记下释放，读取时断言它未被置位，释放后才开始的读取同样会让模型失败
+pub(crate) struct Heap<T> {
+    #[cfg(loom)]
+    value: loom::cell::UnsafeCell<T>,
+    #[cfg(loom)]
+    freed: loom::sync::atomic::AtomicBool,
+    #[cfg(not(loom))]
+    value: T,
+}
+
+impl<T> Heap<T> {
+    // 装箱并转成裸指针，所有权交给调用方
+    #[track_caller]
+    pub(crate) fn into_raw(value: T) -> *mut Heap<T> {
+        #[cfg(loom)]
+        let value = loom::cell::UnsafeCell::new(value);
+        let ptr = Box::into_raw(Box::new(Heap {
+            value,
+            #[cfg(loom)]
+            freed: loom::sync::atomic::AtomicBool::new(false),
+        }));
+        #[cfg(all(debug_assertions, feature = "std"))]
+        tracker::alloc(ptr as usize);
+        ptr
+    }
+
+    // 读取裸指针指向的值；调用方保证 ptr 非空且尚未被释放
+    #[track_caller]
+    pub(crate) unsafe fn with<R>(ptr: *const Heap<T>, f: impl FnOnce(&T) -> R) -> R {
+        #[cfg(all(debug_assertions, feature = "std"))]
+        tracker::read(ptr as usize);
+        #[cfg(loom)]
+        assert!(
+            !(*ptr).freed.load(core::sync::atomic::Ordering::Relaxed),
+            "read of freed pointer {:p}",
+            ptr
+        );
+        #[cfg(loom)]
+        return (*ptr).value.with(|v| f(&*v));
+        #[cfg(not(loom))]
+        f(&(*ptr).value)
+    }
+
+    // 释放 into_raw 得到的指针，空指针直接忽略；调用方保证不会再有人读取它
+    #[track_caller]
+    pub(crate) unsafe fn free(ptr: *mut Heap<T>) {
+        if ptr.is_null() {
+            return;
+        }
+        #[cfg(all(debug_assertions, feature = "std"))]
+        tracker::free(ptr as usize);
+        // loom 下只登记写访问而不真正释放，让有问题的交错里读者仍能访问到它并被 loom 发现
+        #[cfg(loom)]
+        {
+            (*ptr)
+                .freed
+                .store(true, core::sync::atomic::Ordering::Relaxed);
+            (*ptr).value.with_mut(|_| ());
+        }
+        #[cfg(not(loom))]
+        drop(Box::from_raw(ptr));
+    }
+}
+
+#[cfg(all(debug_assertions, feature = "std"))]
+mod tracker {
+    use std::collections::BTreeMap;
+    use std::panic::Location;
+    use std::sync::{Mutex, MutexGuard};
+
+    // 每个地址最近一次的状态及分配、释放的位置；地址被重新分配时覆盖旧记录
+    enum State {
+        Live(&'static Location<'static>),
+        Freed {
+            allocated: &'static Location<'static>,
+            freed: &'static Location<'static>,
+        },
+    }
+
+    static ALLOCATIONS: Mutex<BTreeMap<usize, State>> = Mutex::new(BTreeMap::new());
+
+    fn allocations() -> MutexGuard<'static, BTreeMap<usize, State>> {
+        // 别的线程在检查中 panic 不影响登记表本身
+        ALLOCATIONS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    #[track_caller]
+    pub(super) fn alloc(addr: usize) {
+        allocations().insert(addr, State::Live(Location::caller()));
+    }
+
+    #[track_caller]
+    pub(super) fn read(addr: usize) {
+        let caller = Location::caller();
+        let err = match allocations().get(&addr) {
+            Some(State::Live(_)) => return,
+            Some(State::Freed { allocated, freed }) => format!(
+                "read of freed pointer {:#x} at {} (allocated at {}, freed at {})",
+                addr, caller, allocated, freed
+            ),
+            None => format!("read of unknown pointer {:#x} at {}", addr, caller),
+        };
+        panic!("{}", err);
+    }
+
+    #[track_caller]
+    pub(super) fn free(addr: usize) {
+        let caller = Location::caller();
+        let err = {
+            let mut allocations = allocations();
+            match allocations.get(&addr) {
+                Some(&State::Live(allocated)) => {
+                    let freed = caller;
+                    allocations.insert(addr, State::Freed { allocated, freed });
+                    return;
+                }
+                Some(State::Freed { allocated, freed }) => format!(
+                    "double free of pointer {:#x} at {} (allocated at {}, already freed at {})",
+                    addr, caller, allocated, freed
+                ),
+                None => format!("free of unknown pointer {:#x} at {}", addr, caller),
+            }
+        };
+        panic!("{}", err);
+    }
+}
+
+// 测试直接操作登记表，用静态变量的地址冒充分配，避免真实地址被并发运行的其他测试复用
+#[cfg(all(test, debug_assertions, feature = "std"))]
+#[test]
+#[should_panic(expected = "double free of pointer")]
+fn test_double_free() {
+    static SLOT: u8 = 0;
+    let addr = &SLOT as *const u8 as usize;
+    tracker::alloc(addr);
+    tracker::free(addr);
+    tracker::free(addr);
+}
+
+#[cfg(all(test, debug_assertions, feature = "std"))]
+#[test]
+#[should_panic(expected = "read of freed pointer")]
+fn test_read_after_free() {
+    static SLOT: u8 = 0;
+    let addr = &SLOT as *const u8 as usize;
+    tracker::alloc(addr);
+    tracker::read(addr);
+    tracker::free(addr);
+    tracker::read(addr);
+}
+
+#[cfg(all(test, debug_assertions, feature = "std"))]
+#[test]
+fn test_panic_location() {
+    static SLOT: u8 = 0;
+    let addr = &SLOT as *const u8 as usize;
+    let line = line!() + 1;
+    tracker::alloc(addr);
+    let err = std::panic::catch_unwind(|| {
+        tracker::free(addr);
+        tracker::free(addr);
+    })
+    .unwrap_err();
+    let msg = err.downcast_ref::<String>().unwrap();
+    let at = |line: u32| format!("{}:{}:", file!(), line);
+    assert!(msg.contains(&format!(" at {}", at(line + 3))), "{}", msg);
+    assert!(
+        msg.contains(&format!("allocated at {}", at(line))),
+        "{}",
+        msg
+    );
+    assert!(
+        msg.contains(&format!("already freed at {}", at(line + 2))),
+        "{}",
+        msg
+    );
+}
+
+#[test]
+fn test_heap() {
+    let ptr = Heap::into_raw("value".to_string());
+    assert_eq!(unsafe { Heap::with(ptr, |s| s.clone()) }, "value");
+    unsafe {
+        Heap::free(ptr);
+        Heap::free(std::ptr::null_mut::<Heap<String>>());
+    }
+}
@@ -0,0 +1,103 @@
+// 并发原语的统一入口。平时直接使用标准库；以 --cfg loom 编译时换成 loom 的模拟实现，
+// 由 loom 穷举线程交错来检查 AtomicPtr 相关代码：
+// RUSTFLAGS="--cfg loom" cargo test --release --test loom
+// 没有 std 时原子类型来自 core，Arc 来自 alloc，线程和锁不可用
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::{fence, AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+#[cfg(loom)]
+pub(crate) use loom::sync::{Arc, Mutex};
+#[cfg(loom)]
+pub(crate) use loom::thread;
+
+#[cfg(not(loom))]
+pub(crate) use alloc::sync::Arc;
+#[cfg(not(loom))]
+pub(crate) use core::sync::atomic::{fence, AtomicPtr, AtomicU8, AtomicUsize, Ordering};
+#[cfg(all(not(loom), feature = "std"))]
+pub(crate) use std::sync::Mutex;
+#[cfg(all(not(loom), feature = "std"))]
+pub(crate) use std::thread;
+
+// 退避策略：先自旋，自旋次数到了就让出时间片，让出次数也到了就短暂休眠。
+// 没有 std 时既不能让出也不能休眠，只做指数增长的自旋
+pub(crate) struct Backoff {
+    spin_count: u32,
+    #[cfg(feature = "std")]
+    yield_count: u32,
+}
+
+impl Backoff {
+    pub(crate) fn new() -> Self {
+        Backoff {
+            spin_count: 0,
+            #[cfg(feature = "std")]
+            yield_count: 0,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn snooze(&mut self) {
+        // loom 下每次都必须让出，模型检查器才会去调度其他线程
+        if cfg!(loom) {
+            thread::yield_now();
+            return;
+        }
+        self.spin_count += 1;
+        if self.spin_count >= 30 {
+            self.spin_count = 0;
+            self.yield_count += 1;
+            thread::yield_now();
+            if self.yield_count >= 15 {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+                self.yield_count = 0;
+            }
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn snooze(&mut self) {
+        for _ in 0..1u32 << self.spin_count {
+            core::hint::spin_loop();
+        }
+        if self.spin_count < 6 {
+            self.spin_count += 1;
+        }
+    }
+}
+
+// 读者计数：读者在解引用共享指针期间登记，写者替换指针后等登记归零再释放旧值。
+// 两边都用读-改-写操作，写者看到计数为 0 时，之后才登记的读者一定能看到新指针
+pub(crate) struct Readers {
+    count: AtomicUsize,
+}
+
+pub(crate) struct ReadGuard<'a> {
+    readers: &'a Readers,
+}
+
+impl Readers {
+    pub(crate) fn new() -> Self {
+        Readers {
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn enter(&self) -> ReadGuard<'_> {
+        self.count.fetch_add(1, Ordering::Acquire);
+        ReadGuard { readers: self }
+    }
+
+    // 等待已经登记的读者全部离开，调用前必须已经把旧指针替换掉
+    pub(crate) fn wait(&self) {
+        let mut backoff = Backoff::new();
+        while self.count.fetch_add(0, Ordering::AcqRel) != 0 {
+            backoff.snooze();
+        }
+    }
+}
+
+impl Drop for ReadGuard<'_> {
+    fn drop(&mut self) {
+        self.readers.count.fetch_sub(1, Ordering::Release);
+    }
+}
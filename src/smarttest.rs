@@ -39,6 +39,7 @@ fn display_value_by_box(ss: Box<SimpleStruct>) {
 }
 
 #[derive(Debug)]
+#[allow(dead_code)] // 字段只通过 {:?} 打印
 struct SimpleStruct2 {
     value: i32,
     // 假设我们想让 SimpleStruct 的某个字段在 Rc 环境下仍可变
@@ -53,7 +54,7 @@ fn use_rc() {
     }); // 创建一个引用计数的共享指针
 
     // 克隆引用，增加引用计数
-    let another_ref = Rc::clone(&shared_struct);
+    let _another_ref = Rc::clone(&shared_struct);
 
     // 使用 deref coercion 自动解引用打印
     println!("Rc shared struct: {:?}", shared_struct);
@@ -151,7 +152,7 @@ fn use_raw_pointers() {
 #[test]
 fn test_square() {
     // 定义一个数字列表
-    let mut numbers = vec![1, 2, 3, 4, 5];
+    let numbers = vec![1, 2, 3, 4, 5];
 
     // 定义一个匿名闭包，用于计算平方
     let square = |x: i32| -> i32 { x * x }; // 注意这里类型标注可以省略，Rust会自动推导
@@ -0,0 +1,511 @@
+// 并发测试的公共设施：同时起跑的一组线程和可复现的压力测试。
+//
+// StressRun 让每个线程带着自己的随机数种子反复执行同一个闭包，统计吞吐量和单次耗时；
+// 任一线程 panic 都会让整轮测试停下，报告里带上运行种子，设置 STRESS_SEED 即可按原样重跑：
+// STRESS_SEED=0x1234 cargo test testvs
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Barrier;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// 固定数量的一组线程，所有线程在起跑线（Barrier）上等齐后同时开始执行，尽量制造竞争。
+// 不是线程池：每次 broadcast 都新建一组作用域线程，调用结束时全部退出
+pub struct ThreadGroup {
+    size: usize,
+}
+
+impl ThreadGroup {
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0, "thread group size must be positive");
+        ThreadGroup { size }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    // 在每个线程上执行一次 f(线程序号)，按序号返回各线程的结果；
+    // 线程是作用域线程，f 可以直接借用调用方栈上的数据，单个线程 panic 不影响其他线程
+    pub fn broadcast<F, R>(&self, f: F) -> Vec<thread::Result<R>>
+    where
+        F: Fn(usize) -> R + Sync,
+        R: Send,
+    {
+        let barrier = Barrier::new(self.size);
+        thread::scope(|s| {
+            let handles: Vec<_> = (0..self.size)
+                .map(|index| {
+                    let (f, barrier) = (&f, &barrier);
+                    s.spawn(move || {
+                        barrier.wait();
+                        f(index)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join()).collect()
+        })
+    }
+}
+
+// SplitMix64：状态只有一个 u64，足够给测试生成可复现的随机数
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // [0, n) 内的随机数，n 必须大于 0
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "empty range");
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+
+    // 从切片中随机取一个元素
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+}
+
+// 传给测试闭包的线程上下文
+pub struct Worker {
+    index: usize,
+    iteration: u64,
+    seed: u64,
+    rng: SplitMix64,
+}
+
+impl Worker {
+    // 线程序号，从 0 开始，可用来给线程分配读者、写者等不同角色
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    // 本线程已经完成的迭代次数，即当前是第几次迭代（从 0 开始）
+    pub fn iteration(&self) -> u64 {
+        self.iteration
+    }
+
+    // 本线程的种子，由运行种子和线程序号决定
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn rng(&mut self) -> &mut SplitMix64 {
+        &mut self.rng
+    }
+}
+
+const DEFAULT_ITERATIONS: u64 = 1000;
+
+// 压力测试的配置。迭代次数和持续时间都设置时，先到者为准；
+// 都不设置时每个线程执行 1000 次
+pub struct StressRun {
+    threads: usize,
+    iterations: Option<u64>,
+    duration: Option<Duration>,
+    seed: u64,
+}
+
+impl Default for StressRun {
+    fn default() -> Self {
+        StressRun::new()
+    }
+}
+
+impl StressRun {
+    // 线程数默认取 CPU 核数；种子优先取环境变量 STRESS_SEED，否则由当前时间生成
+    pub fn new() -> Self {
+        StressRun::with_seed(seed_from_env().unwrap_or_else(random_seed))
+    }
+
+    // 使用给定的运行种子，不读取 STRESS_SEED，结果不受环境影响
+    pub fn with_seed(seed: u64) -> Self {
+        let threads = thread::available_parallelism().map_or(4, |n| n.get());
+        StressRun {
+            threads,
+            iterations: None,
+            duration: None,
+            seed,
+        }
+    }
+
+    // 至少一个线程，传入 0 时按 1 处理
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    // 每个线程的迭代次数
+    pub fn iterations(mut self, iterations: u64) -> Self {
+        self.iterations = Some(iterations);
+        self
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    // 固定运行种子；设置了 STRESS_SEED 时以环境变量为准，方便不改代码就能重跑失败的种子
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed_from_env().unwrap_or(seed);
+        self
+    }
+
+    // 各线程同时起跑，反复执行 f 直到达到迭代次数或持续时间，或有线程失败
+    pub fn run<F>(&self, f: F) -> StressReport
+    where
+        F: Fn(&mut Worker) + Sync,
+    {
+        let iterations = match (self.iterations, self.duration) {
+            (None, None) => DEFAULT_ITERATIONS,
+            (iterations, _) => iterations.unwrap_or(u64::MAX),
+        };
+        let stop = AtomicBool::new(false);
+        let group = ThreadGroup::new(self.threads);
+        let started = Instant::now();
+
+        let results = group.broadcast(|index| {
+            let seed = thread_seed(self.seed, index);
+            let mut worker = Worker {
+                index,
+                iteration: 0,
+                seed,
+                rng: SplitMix64::new(seed),
+            };
+            let mut latency = Latency::default();
+            let deadline = self.duration.map(|d| Instant::now() + d);
+            while worker.iteration < iterations && !stop.load(Ordering::Relaxed) {
+                if deadline.is_some_and(|d| Instant::now() >= d) {
+                    break;
+                }
+                let start = Instant::now();
+                let res = panic::catch_unwind(AssertUnwindSafe(|| f(&mut worker)));
+                latency.record(start.elapsed());
+                if let Err(payload) = res {
+                    stop.store(true, Ordering::Relaxed);
+                    let failure = Failure {
+                        thread: index,
+                        iteration: worker.iteration,
+                        seed,
+                        message: panic_message(&*payload),
+                    };
+                    return (latency, Some(failure));
+                }
+                worker.iteration += 1;
+            }
+            (latency, None)
+        });
+
+        let elapsed = started.elapsed();
+        let mut latency = Latency::default();
+        let mut failures = Vec::new();
+        for (latency_of_thread, failure) in results.into_iter().map(|r| r.unwrap()) {
+            latency.merge(&latency_of_thread);
+            failures.extend(failure);
+        }
+        StressReport {
+            threads: self.threads,
+            seed: self.seed,
+            elapsed,
+            latency,
+            failures,
+        }
+    }
+}
+
+fn seed_from_env() -> Option<u64> {
+    let s = std::env::var("STRESS_SEED").ok()?;
+    let s = s.trim();
+    match s.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+    SplitMix64::new(nanos).next_u64()
+}
+
+// 线程种子只取决于运行种子和线程序号，同样的运行种子和线程数能复现同样的随机序列
+fn thread_seed(seed: u64, index: usize) -> u64 {
+    SplitMix64::new(seed ^ (index as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)).next_u64()
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+// 某个线程的一次失败，带上重现所需的种子
+#[derive(Clone, Debug)]
+pub struct Failure {
+    pub thread: usize,
+    pub iteration: u64,
+    pub seed: u64,
+    pub message: String,
+}
+
+// 单次迭代耗时的统计。按 2 的幂分桶记录，不必保存每个样本，百分位数是所在桶的上界
+#[derive(Clone, Debug)]
+pub struct Latency {
+    count: u64,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+    buckets: [u64; 64],
+}
+
+impl Default for Latency {
+    fn default() -> Self {
+        Latency {
+            count: 0,
+            total: Duration::ZERO,
+            min: Duration::MAX,
+            max: Duration::ZERO,
+            buckets: [0; 64],
+        }
+    }
+}
+
+impl Latency {
+    fn record(&mut self, d: Duration) {
+        let nanos = d.as_nanos().min(u64::MAX as u128) as u64;
+        self.count += 1;
+        self.total += d;
+        self.min = self.min.min(d);
+        self.max = self.max.max(d);
+        self.buckets[(64 - nanos.leading_zeros()).min(63) as usize] += 1;
+    }
+
+    fn merge(&mut self, other: &Latency) {
+        self.count += other.count;
+        self.total += other.total;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        for (a, b) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *a += b;
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn min(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            self.min
+        }
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.total.as_nanos() / self.count as u128) as u64)
+        }
+    }
+
+    // p 取 0.0 ~ 1.0，例如 0.99 表示 p99
+    pub fn percentile(&self, p: f64) -> Duration {
+        let rank = ((self.count as f64 * p).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, &n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                // 第 i 个桶存放 [2^(i-1), 2^i) 纳秒
+                let upper =
+                    Duration::from_nanos(1u64.checked_shl(i as u32).map_or(u64::MAX, |v| v - 1));
+                return upper.clamp(self.min(), self.max);
+            }
+        }
+        self.max
+    }
+}
+
+// 一轮压力测试的结果
+#[derive(Debug)]
+pub struct StressReport {
+    pub threads: usize,
+    pub seed: u64,
+    pub elapsed: Duration,
+    pub latency: Latency,
+    pub failures: Vec<Failure>,
+}
+
+impl StressReport {
+    // 所有线程完成的迭代总数（包括失败的那次）
+    pub fn operations(&self) -> u64 {
+        self.latency.count
+    }
+
+    // 每秒完成的迭代次数
+    pub fn throughput(&self) -> f64 {
+        self.operations() as f64 / self.elapsed.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    // 有失败时 panic，消息中带上重跑用的 STRESS_SEED
+    #[track_caller]
+    pub fn assert_ok(&self) -> &Self {
+        if !self.is_ok() {
+            panic!("{}", self);
+        }
+        self
+    }
+}
+
+impl fmt::Display for StressReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} threads, {} ops in {:?} ({:.0} ops/s), latency min {:?} p50 {:?} p99 {:?} max {:?}",
+            self.threads,
+            self.operations(),
+            self.elapsed,
+            self.throughput(),
+            self.latency.min(),
+            self.latency.percentile(0.5),
+            self.latency.percentile(0.99),
+            self.latency.max(),
+        )?;
+        if self.failures.is_empty() {
+            return Ok(());
+        }
+        write!(
+            f,
+            "\n{} thread(s) failed, rerun with STRESS_SEED={:#x} and {} threads:",
+            self.failures.len(),
+            self.seed,
+            self.threads
+        )?;
+        for failure in &self.failures {
+            write!(
+                f,
+                "\n  thread {} at iteration {} (thread seed {:#x}): {}",
+                failure.thread, failure.iteration, failure.seed, failure.message
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_thread_group() {
+    let group = ThreadGroup::new(4);
+    let mut results: Vec<_> = group
+        .broadcast(|index| index * 10)
+        .into_iter()
+        .map(|r| r.unwrap())
+        .collect();
+    results.sort();
+    assert_eq!(results, [0, 10, 20, 30]);
+
+    let results = group.broadcast(|index| assert_ne!(index, 2));
+    assert!(results[2].is_err());
+    assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 3);
+}
+
+#[test]
+fn test_split_mix() {
+    let mut a = SplitMix64::new(42);
+    let mut b = SplitMix64::new(42);
+    let xs: Vec<_> = (0..8).map(|_| a.next_u64()).collect();
+    let ys: Vec<_> = (0..8).map(|_| b.next_u64()).collect();
+    assert_eq!(xs, ys);
+    assert_ne!(thread_seed(42, 0), thread_seed(42, 1));
+    assert!((0..1000).all(|_| a.below(7) < 7));
+}
+
+#[test]
+fn test_stress_run() {
+    use std::sync::atomic::AtomicU64;
+
+    let counter = AtomicU64::new(0);
+    let report = StressRun::new().threads(4).iterations(250).run(|_| {
+        counter.fetch_add(1, Ordering::Relaxed);
+    });
+    report.assert_ok();
+    assert_eq!(counter.load(Ordering::Relaxed), 1000);
+    assert_eq!(report.operations(), 1000);
+    assert!(report.latency.percentile(0.5) <= report.latency.percentile(0.99));
+    assert!(report.latency.percentile(0.99) <= report.latency.max());
+
+    // 只设置持续时间时一直跑到时间用完
+    let report = StressRun::new()
+        .threads(2)
+        .duration(Duration::from_millis(20))
+        .run(|_| {});
+    assert!(report.is_ok());
+    assert!(report.elapsed >= Duration::from_millis(20));
+
+    // 线程数为 0 时按 1 个线程运行
+    let report = StressRun::new().threads(0).iterations(10).run(|_| {});
+    assert_eq!(report.threads, 1);
+    assert_eq!(report.operations(), 10);
+}
+
+#[test]
+fn test_stress_failure_reports_seed() {
+    // 同样的种子得到同样的线程种子和随机序列，失败可以原样重现
+    let run = || {
+        StressRun::with_seed(7)
+            .threads(2)
+            .iterations(1000)
+            .run(|w| {
+                let n = w.rng().below(100);
+                assert!(!(w.index() == 1 && n == 99), "unlucky {}", n);
+            })
+    };
+    let (first, second) = (run(), run());
+    let failure = &first.failures[0];
+    assert_eq!(failure.thread, 1);
+    assert_eq!(failure.message, "unlucky 99");
+    assert_eq!(failure.iteration, second.failures[0].iteration);
+    assert_eq!(first.seed, 7);
+    assert_eq!(failure.seed, thread_seed(7, 1));
+
+    let msg = first.to_string();
+    assert!(
+        msg.contains(&format!("STRESS_SEED={:#x}", first.seed)),
+        "{}",
+        msg
+    );
+    let err = panic::catch_unwind(|| {
+        first.assert_ok();
+    })
+    .unwrap_err();
+    assert!(panic_message(&*err).contains("unlucky 99"));
+}
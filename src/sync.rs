@@ -0,0 +1,8 @@
+// 对外公开的并发原语
+pub use crate::atomic1::SharedData;
+#[cfg(feature = "std")]
+pub use crate::atomic2::ThreadSafeData;
+pub use crate::atomic3::SharedString;
+pub use crate::atomic_string::AtomicString;
+#[cfg(feature = "std")]
+pub use crate::interner::{AtomicSymbol, Symbol};
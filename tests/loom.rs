@@ -0,0 +1,205 @@
+// loom 模型检查：穷举 update / get / drop 的线程交错
+// RUSTFLAGS="--cfg loom" cargo test --release --test loom
+#![cfg(loom)]
+
+use loom::sync::Arc;
+use loom::thread;
+use rstut::sync::{AtomicString, SharedString, ThreadSafeData};
+
+fn model<F>(f: F)
+where
+    F: Fn() + Sync + Send + 'static,
+{
+    let mut builder = loom::model::Builder::new();
+    // 读者和写者都有自旋等待，默认的分支上限不够用
+    builder.max_branches = 100_000;
+    // 限制抢占次数，绝大多数并发 bug 在 2~3 次抢占内就能复现
+    if builder.preemption_bound.is_none() {
+        builder.preemption_bound = Some(3);
+    }
+    builder.check(f);
+}
+
+// 写者释放旧值时读者可能还在读它（释放后读）
+#[test]
+fn atomic_string_update_get() {
+    model(|| {
+        let vstring = Arc::new(AtomicString::with_inline_capacity("Initial".to_string(), 0));
+
+        let writer = {
+            let vs = vstring.clone();
+            thread::spawn(move || vs.update("Updated".to_string()))
+        };
+        let value = vstring.get();
+        assert!(value == "Initial" || value == "Updated", "{}", value);
+
+        writer.join().unwrap();
+        assert_eq!(vstring.get(), "Updated");
+    });
+}
+
+// 值和版本号必须一起变化，不能读到新值配旧版本号
+#[test]
+fn atomic_string_version() {
+    model(|| {
+        let vstring = Arc::new(AtomicString::with_inline_capacity("v0".to_string(), 0));
+
+        let writer = {
+            let vs = vstring.clone();
+            thread::spawn(move || {
+                vs.update("v1".to_string());
+                vs.update("v2".to_string());
+            })
+        };
+        let (value, version) = vstring.snapshot();
+        assert_eq!(value, format!("v{}", version));
+
+        writer.join().unwrap();
+        assert_eq!(vstring.version(), 2);
+    });
+}
+
+// 两个写者同时更新，版本号不能丢失
+#[test]
+fn atomic_string_concurrent_updates() {
+    model(|| {
+        let vstring = Arc::new(AtomicString::with_inline_capacity("v0".to_string(), 0));
+
+        let writers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|v| {
+                let vs = vstring.clone();
+                thread::spawn(move || vs.update(v.to_string()))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(vstring.version(), 2);
+        let value = vstring.get();
+        assert!(value == "a" || value == "b", "{}", value);
+    });
+}
+
+// 内联缓冲区不能读到写了一半的值
+#[test]
+fn atomic_string_inline() {
+    model(|| {
+        let vstring = Arc::new(AtomicString::with_inline_capacity("xy".to_string(), 2));
+
+        let writer = {
+            let vs = vstring.clone();
+            thread::spawn(move || vs.update_str("ab"))
+        };
+        let value = vstring.get();
+        assert!(value == "xy" || value == "ab", "{}", value);
+
+        writer.join().unwrap();
+    });
+}
+
+#[test]
+fn atomic_string_update_fair() {
+    model(|| {
+        let vstring = Arc::new(AtomicString::with_inline_capacity("v0".to_string(), 0));
+
+        let writers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|v| {
+                let vs = vstring.clone();
+                thread::spawn(move || {
+                    vs.update_fair(v.to_string());
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(vstring.version(), 2);
+        let value = vstring.get();
+        assert!(value == "a" || value == "b", "{}", value);
+    });
+}
+
+// 最后一个持有者在任意线程上释放
+#[test]
+fn atomic_string_drop() {
+    model(|| {
+        let vstring = Arc::new(AtomicString::with_inline_capacity("Initial".to_string(), 0));
+
+        let handle = {
+            let vs = vstring.clone();
+            thread::spawn(move || {
+                vs.update("Updated".to_string());
+                drop(vs);
+            })
+        };
+        let _ = vstring.get();
+        drop(vstring);
+
+        handle.join().unwrap();
+    });
+}
+
+#[test]
+fn shared_string_update_get() {
+    model(|| {
+        let shared_str = Arc::new(SharedString::new("Initial".to_string()));
+
+        let writer = {
+            let s = shared_str.clone();
+            thread::spawn(move || s.update("Updated".to_string()))
+        };
+        let value = shared_str.get();
+        assert!(value == "Initial" || value == "Updated", "{}", value);
+
+        writer.join().unwrap();
+        assert_eq!(shared_str.get(), "Updated");
+    });
+}
+
+#[test]
+fn shared_string_concurrent_updates() {
+    model(|| {
+        let shared_str = Arc::new(SharedString::new("Initial".to_string()));
+
+        let writers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|v| {
+                let s = shared_str.clone();
+                thread::spawn(move || s.update(v.to_string()))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        let value = shared_str.get();
+        assert!(value == "a" || value == "b", "{}", value);
+    });
+}
+
+#[test]
+fn thread_safe_data() {
+    model(|| {
+        let shared_data = Arc::new(ThreadSafeData::new("Initial".to_string()));
+
+        let handle = {
+            let data_ref = shared_data.clone();
+            thread::spawn(move || {
+                data_ref.increment_counter();
+                data_ref.update_message("Updated by thread");
+            })
+        };
+        shared_data.increment_counter();
+        assert_eq!(shared_data.get_pre(), "AtomicPtr>>>");
+        let message = shared_data.get_message();
+        assert!(message == "Initial" || message == "Updated by thread");
+
+        handle.join().unwrap();
+        assert_eq!(shared_data.get_counter(), 2);
+        assert_eq!(shared_data.get_message(), "Updated by thread");
+    });
+}
@@ -0,0 +1,49 @@
+// 关闭 std 特性后仍然可用的部分（no_std + alloc）：
+// cargo test --no-default-features --test no_std
+use rstut::sync::{AtomicString, SharedData, SharedString};
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn atomic_string() {
+    let vstring = Arc::new(AtomicString::new("ok".to_string()));
+
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            let vs = vstring.clone();
+            thread::spawn(move || {
+                for n in 0..100 {
+                    vs.update_str(if (i + n) % 2 == 0 { "ok" } else { "degraded" });
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(vstring.version(), 400);
+    assert!(vstring.get() == "ok" || vstring.get() == "degraded");
+    assert_eq!(vstring.try_update("down".to_string()), Ok(()));
+
+    let (value, version) = vstring.snapshot();
+    assert_eq!((value.as_str(), version), ("down", 401));
+}
+
+#[test]
+fn shared_string() {
+    let shared_str = SharedString::from("Initial Value");
+    shared_str.update("Value-1".to_string());
+    assert_eq!(shared_str.get(), "Value-1");
+    assert_eq!(shared_str.clone().to_string(), "Value-1");
+}
+
+#[test]
+fn shared_data() {
+    let shared_data = SharedData::new("Initial Message");
+    for _ in 0..10 {
+        shared_data.increment_counter();
+    }
+    assert_eq!(shared_data.get_counter(), 10);
+    assert_eq!(shared_data.get_message(), "Initial Message");
+}
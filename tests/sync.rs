@@ -0,0 +1,127 @@
+// 以外部使用者的身份测试 rstut::sync 中公开的并发原语
+#![cfg(feature = "std")]
+
+use rstut::sync::{AtomicString, AtomicSymbol, SharedData, SharedString, Symbol, ThreadSafeData};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn atomic_string() {
+    let vstring = Arc::new(AtomicString::new("Initial Message".to_string()));
+
+    let handles: Vec<_> = (0..10)
+        .map(|i| {
+            let vs = vstring.clone();
+            thread::spawn(move || {
+                vs.update(format!("Value updated {}", i));
+                vs.update_fair(format!("Fair value {}", i));
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let (value, version) = vstring.snapshot();
+    assert!(value.starts_with("Fair value "));
+    assert_eq!(version, 20);
+
+    assert_eq!(vstring.try_update("ok".to_string()), Ok(()));
+    assert_eq!(
+        vstring.try_update_for("degraded".to_string(), Duration::from_millis(10)),
+        Ok(())
+    );
+    let mut buf = String::new();
+    vstring.get_into(&mut buf);
+    assert_eq!(buf, "degraded");
+    assert_eq!(vstring.version(), 22);
+
+    let small = AtomicString::with_inline_capacity("a".to_string(), 4);
+    small.update_str("longer than four");
+    assert_eq!(small.get(), "longer than four");
+    assert_eq!(format!("{}", small), "longer than four");
+}
+
+#[test]
+fn shared_string() {
+    let shared_str = Arc::new(SharedString::new("Initial Value".to_string()));
+
+    let handles: Vec<_> = (0..10)
+        .map(|i| {
+            let s = shared_str.clone();
+            thread::spawn(move || s.update(format!("Value-{}", i)))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert!(shared_str.get().starts_with("Value-"));
+}
+
+#[test]
+fn shared_data() {
+    let shared_data = SharedData::new("Initial Message");
+
+    let handles: Vec<_> = (0..10)
+        .map(|_| {
+            let data_ref = shared_data.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    data_ref.increment_counter();
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(shared_data.get_counter(), 1000);
+    assert_eq!(shared_data.get_message(), "Initial Message");
+}
+
+#[test]
+fn thread_safe_data() {
+    let shared_data = Arc::new(ThreadSafeData::new("Initial Message".to_string()));
+
+    let handles: Vec<_> = (0..10)
+        .map(|_| {
+            let data_ref = shared_data.clone();
+            thread::spawn(move || {
+                for _ in 0..100 {
+                    data_ref.increment_counter();
+                }
+                data_ref.update_message("Updated by thread");
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(shared_data.get_counter(), 1000);
+    assert_eq!(shared_data.get_message(), "Updated by thread");
+    assert_eq!(shared_data.get_pre(), "AtomicPtr>>>");
+}
+
+#[test]
+fn atomic_symbol() {
+    let region = Arc::new(AtomicSymbol::new(Symbol::intern("cn-north")));
+
+    let handles: Vec<_> = ["cn-north", "cn-south", "us-east"]
+        .into_iter()
+        .map(|name| {
+            let r = region.clone();
+            thread::spawn(move || r.update(name))
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let current = region.load();
+    assert_eq!(Symbol::intern(current.as_str()), current);
+    assert!(["cn-north", "cn-south", "us-east"].contains(&region.get()));
+}